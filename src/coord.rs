@@ -4,11 +4,32 @@ pub type Vi = isize;
 pub type Vf = f64;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct V2i(pub Vi, pub Vi);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct V2f(pub Vf, pub Vf);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/* Compass directions with +y downward, so N is V2i(0, -1). */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct R2i {
     origin: V2i,
@@ -21,12 +42,34 @@ pub struct R2f {
     dim: V2f,
 }
 
+/* A distance on the integer lattice, selected at the type level by a topology marker. */
+pub trait Metric {
+    fn norm(v: V2i) -> Vi;
+
+    fn dist(a: V2i, b: V2i) -> Vi { Self::norm(a - b) }
+}
+
 impl V2i {
+    pub const ZERO: V2i = V2i(0, 0);
+    pub const ONE: V2i = V2i(1, 1);
+    pub const X: V2i = V2i(1, 0);
+    pub const Y: V2i = V2i(0, 1);
+
     pub fn l1(self) -> Vi { self.0.abs() + self.1.abs() }
     pub fn l2_sq(self) -> Vi { self.0 * self.0 + self.1 * self.1 }
+    pub fn dot(self, other: V2i) -> Vi { self.0 * other.0 + self.1 * other.1 }
+    pub fn cross(self, other: V2i) -> Vi { self.0 * other.1 - self.1 * other.0 }
     pub fn linf(self) -> Vi { self.0.abs().max(self.1.abs()) }
+    pub fn l2_ceil(self) -> Vi {
+        let sq = self.l2_sq();
+        let mut r = (sq as Vf).sqrt().ceil() as Vi;
+        while r * r < sq { r += 1; }
+        while r > 0 && (r - 1) * (r - 1) >= sq { r -= 1; }  // NB: Correct any float rounding
+        r
+    }
     pub fn swap(self) -> V2i { V2i(self.1, self.0) }
     pub fn abs(self) -> V2i { V2i(self.0.abs(), self.1.abs()) }
+    pub fn signum(self) -> V2i { V2i(self.0.signum(), self.1.signum()) }
     pub fn div_euclid(self, other: V2i) -> V2i { V2i(self.0.div_euclid(other.0), self.1.div_euclid(other.1)) }
     pub fn rem_euclid(self, other: V2i) -> V2i { V2i(self.0.rem_euclid(other.0), self.1.rem_euclid(other.1)) }
     pub fn is_q1(self) -> bool { self.0 >= 0 && self.1 >= 0 }
@@ -35,16 +78,60 @@ impl V2i {
     pub fn cmax(self) -> Vi { self.0.max(self.1) }
     pub fn min(self, other: V2i) -> V2i { V2i(self.0.min(other.0), self.1.min(other.1)) }
     pub fn max(self, other: V2i) -> V2i { V2i(self.0.max(other.0), self.1.max(other.1)) }
+    /* Unlike Ord::clamp this never panics: a component with lo > hi comes out as hi. */
+    pub fn clamp(self, lo: V2i, hi: V2i) -> V2i { self.max(lo).min(hi) }
+
+    /* Quarter turns about the origin, clockwise as displayed with +y downward (as Grid::rotate):
+     * rot_cw takes +x to +y. */
+    pub fn rot_cw(self) -> V2i { V2i(-self.1, self.0) }
+    pub fn rot_ccw(self) -> V2i { V2i(self.1, -self.0) }
+    pub fn lerp_round(self, other: V2i, t: Vf) -> V2i {
+        let p = V2f::from(self).lerp(V2f::from(other), t);
+        V2i(p.0.round() as Vi, p.1.round() as Vi)
+    }
+
+    /* Both start at +x and turn toward +y: +x, +y, -x, -y for the cardinals. */
+    pub fn neighbors4(self) -> [V2i; 4] {
+        [V2i::X, V2i::Y, -V2i::X, -V2i::Y].map(|d| self + d)
+    }
+    pub fn neighbors8(self) -> [V2i; 8] {
+        [
+            V2i(1, 0), V2i(1, 1), V2i(0, 1), V2i(-1, 1),
+            V2i(-1, 0), V2i(-1, -1), V2i(0, -1), V2i(1, -1),
+        ].map(|d| self + d)
+    }
 }
 
 impl V2f {
+    pub const ZERO: V2f = V2f(0.0, 0.0);
+    pub const ONE: V2f = V2f(1.0, 1.0);
+    pub const X: V2f = V2f(1.0, 0.0);
+    pub const Y: V2f = V2f(0.0, 1.0);
+
     pub fn l1(self) -> Vf { self.0.abs() + self.1.abs() }
     pub fn l2_sq(self) -> Vf { self.0 * self.0 + self.1 * self.1 }
+    pub fn dot(self, other: V2f) -> Vf { self.0 * other.0 + self.1 * other.1 }
+    pub fn cross(self, other: V2f) -> Vf { self.0 * other.1 - self.1 * other.0 }
     pub fn l2(self) -> Vf { self.l2_sq().sqrt() }
     pub fn linf(self) -> Vf { self.0.abs().max(self.1.abs()) }
     pub fn swap(self) -> V2f { V2f(self.1, self.0) }
     pub fn abs(self) -> V2f { V2f(self.0.abs(), self.1.abs()) }
+    /* As V2i::signum, so a zero component (of either sign) maps to 0.0; NaN stays NaN. */
+    pub fn signum(self) -> V2f {
+        let sgn = |x: Vf| if x == 0.0 { 0.0 } else { x.signum() };
+        V2f(sgn(self.0), sgn(self.1))
+    }
     pub fn ang(self) -> Vf { self.1.atan2(self.0) }
+    pub fn from_angle(theta: Vf) -> V2f { V2f(theta.cos(), theta.sin()) }
+    pub fn from_angle_len(theta: Vf, len: Vf) -> V2f { V2f::from_angle(theta) * len }
+    pub fn lerp(self, other: V2f, t: Vf) -> V2f { self + (other - self) * t }
+    pub fn powf(self, exp: Vf) -> V2f { V2f(self.0.powf(exp), self.1.powf(exp)) }
+    pub fn sqrt(self) -> V2f { V2f(self.0.sqrt(), self.1.sqrt()) }
+    /* Signed, in (-PI, PI]; antiparallel vectors give PI even when cross is -0.0. */
+    pub fn angle_to(self, other: V2f) -> Vf {
+        let a = self.cross(other).atan2(self.dot(other));
+        if a == -std::f64::consts::PI { std::f64::consts::PI } else { a }
+    }
     pub fn div_euclid(self, other: V2f) -> V2f { V2f(self.0.div_euclid(other.0), self.1.div_euclid(other.1)) }
     pub fn rem_euclid(self, other: V2f) -> V2f { V2f(self.0.rem_euclid(other.0), self.1.rem_euclid(other.1)) }
     pub fn is_q1(self) -> bool { self.0 >= 0.0 && self.1 >= 0.0 }
@@ -53,6 +140,15 @@ impl V2f {
     pub fn cmax(self) -> Vf { self.0.max(self.1) }
     pub fn min(self, other: V2f) -> V2f { V2f(self.0.min(other.0), self.1.min(other.1)) }
     pub fn max(self, other: V2f) -> V2f { V2f(self.0.max(other.0), self.1.max(other.1)) }
+    /* Unlike f64::clamp this never panics: a component with lo > hi comes out as hi. */
+    pub fn clamp(self, lo: V2f, hi: V2f) -> V2f { self.max(lo).min(hi) }
+
+    /* The zero vector has no direction, so it normalizes (and rescales) to itself. */
+    pub fn normalize(self) -> V2f {
+        let len = self.l2();
+        if len == 0.0 { self } else { self / len }
+    }
+    pub fn with_length(self, len: Vf) -> V2f { self.normalize() * len }
 }
 
 impl From<V2i> for V2f {
@@ -63,6 +159,83 @@ impl From<V2f> for V2i {
     fn from(v: V2f) -> V2i { V2i(v.0 as Vi, v.1 as Vi) }
 }
 
+macro_rules! impl_tuple_conv {
+    ($vec:tt, $scalar:tt) => {
+        impl From<($scalar, $scalar)> for $vec {
+            fn from(t: ($scalar, $scalar)) -> $vec { $vec(t.0, t.1) }
+        }
+
+        impl From<[$scalar; 2]> for $vec {
+            fn from(a: [$scalar; 2]) -> $vec { $vec(a[0], a[1]) }
+        }
+
+        impl From<$vec> for ($scalar, $scalar) {
+            fn from(v: $vec) -> ($scalar, $scalar) { (v.0, v.1) }
+        }
+
+        impl From<$vec> for [$scalar; 2] {
+            fn from(v: $vec) -> [$scalar; 2] { [v.0, v.1] }
+        }
+    }
+}
+
+impl_tuple_conv!(V2i, Vi);
+impl_tuple_conv!(V2f, Vf);
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::N, Direction::NE, Direction::E, Direction::SE,
+        Direction::S, Direction::SW, Direction::W, Direction::NW,
+    ];
+
+    pub fn step(self) -> V2i {
+        match self {
+            Direction::N => V2i(0, -1),
+            Direction::NE => V2i(1, -1),
+            Direction::E => V2i(1, 0),
+            Direction::SE => V2i(1, 1),
+            Direction::S => V2i(0, 1),
+            Direction::SW => V2i(-1, 1),
+            Direction::W => V2i(-1, 0),
+            Direction::NW => V2i(-1, -1),
+        }
+    }
+
+    /* None unless step is one of the eight unit moves. */
+    pub fn from_step(step: V2i) -> Option<Direction> {
+        Direction::ALL.iter().copied().find(|d| d.step() == step)
+    }
+}
+
+macro_rules! impl_index {
+    ($vec:tt, $scalar:tt) => {
+        /* 0 is x and 1 is y; anything else panics, as out-of-range slice indexing does. */
+        impl Index<usize> for $vec {
+            type Output = $scalar;
+            fn index(&self, axis: usize) -> &$scalar {
+                match axis {
+                    0 => &self.0,
+                    1 => &self.1,
+                    _ => panic!("index out of bounds: the len is 2 but the index is {}", axis),
+                }
+            }
+        }
+
+        impl IndexMut<usize> for $vec {
+            fn index_mut(&mut self, axis: usize) -> &mut $scalar {
+                match axis {
+                    0 => &mut self.0,
+                    1 => &mut self.1,
+                    _ => panic!("index out of bounds: the len is 2 but the index is {}", axis),
+                }
+            }
+        }
+    }
+}
+
+impl_index!(V2i, Vi);
+impl_index!(V2f, Vf);
+
 macro_rules! generic_rect {
     ($rect:tt, $vec:tt, $scalar:tt) => {
         impl $rect {
@@ -96,7 +269,18 @@ macro_rules! generic_rect {
                 let opp = self.opp();
                 pt.0 >= self.origin.0 && pt.0 < opp.0 && pt.1 >= self.origin.1 && pt.1 < opp.1
             }
+            /* Same half-open test as contains, so every point from iter() is contained. */
+            pub fn contains_point(&self, pt: $vec) -> bool { self.contains(pt) }
+            /* Componentwise bounds check on origin and opp, so an empty other only needs to sit
+             * within self's closed bounds. */
+            pub fn contains_rect(&self, other: $rect) -> bool {
+                let (opp, oopp) = (self.opp(), other.opp());
+                other.origin.0 >= self.origin.0 && other.origin.1 >= self.origin.1
+                    && oopp.0 <= opp.0 && oopp.1 <= opp.1
+            }
 
+            /* Strict: rects that only share an edge or corner (or either of which is empty) have
+             * no intersection. */
             pub fn intersect(&self, other: $rect) -> Option<$rect> {
                 let orig = self.origin.max(other.origin);
                 let opp = self.opp().min(other.opp());
@@ -108,6 +292,32 @@ macro_rules! generic_rect {
                 }
             }
 
+            /* As intersect, but rects meeting along an edge or at a corner give the degenerate
+             * (zero-width or zero-height) shared boundary rather than None. */
+            pub fn intersect_inclusive(&self, other: $rect) -> Option<$rect> {
+                let orig = self.origin.max(other.origin);
+                let opp = self.opp().min(other.opp());
+                let dim = opp - orig;
+                if dim.is_q1() {
+                    Some($rect::origin_dim(orig, dim))
+                } else {
+                    None
+                }
+            }
+
+            /* Same answer as intersect(other).is_some(), without building the rect; so touching
+             * edges don't count as overlapping. */
+            pub fn overlaps(&self, other: $rect) -> bool {
+                let (opp, oopp) = (self.opp(), other.opp());
+                self.origin.0.max(other.origin.0) < opp.0.min(oopp.0)
+                    && self.origin.1.max(other.origin.1) < opp.1.min(oopp.1)
+            }
+
+            /* Zero whenever overlaps is false, edge-touching included. */
+            pub fn intersection_area(&self, other: $rect) -> $scalar {
+                self.intersect(other).map_or(0 as $scalar, |r| r.area())
+            }
+
             pub fn union(&self, other: $rect) -> $rect {
                 $rect::origin_opp(self.origin.min(other.origin), self.opp().max(other.opp()))
             }
@@ -121,7 +331,29 @@ macro_rules! generic_rect {
 
             pub fn origin(&self) -> $vec { self.origin }
             pub fn dim(&self) -> $vec { self.dim }
+            /* The exclusive far corner, one past the last contained point on each axis. */
             pub fn opp(&self) -> $vec { self.origin + self.dim }
+
+            /* Longer side over shorter, so always >= 1; infinite if either side is zero. */
+            pub fn aspect_ratio(&self) -> f64 {
+                let (lo, hi) = (self.dim.cmin() as f64, self.dim.cmax() as f64);
+                if lo == 0.0 { f64::INFINITY } else { hi / lo }
+            }
+
+            pub fn area(&self) -> $scalar { self.dim.0 * self.dim.1 }
+            /* Length of the boundary in unit edges, not the number of boundary tiles. */
+            pub fn perimeter(&self) -> $scalar { (self.dim.0 + self.dim.1) * (2 as $scalar) }
+        }
+
+        /* Like scale, these resize dim about a fixed origin; the origin itself is not scaled. */
+        impl Mul<$scalar> for $rect {
+            type Output = $rect;
+            fn mul(self, rhs: $scalar) -> $rect { $rect::origin_dim(self.origin, self.dim * rhs) }
+        }
+
+        impl Div<$scalar> for $rect {
+            type Output = $rect;
+            fn div(self, rhs: $scalar) -> $rect { $rect::origin_dim(self.origin, self.dim / rhs) }
         }
     }
 }
@@ -129,6 +361,24 @@ macro_rules! generic_rect {
 generic_rect!(R2i, V2i, Vi);
 generic_rect!(R2f, V2f, Vf);
 
+impl R2f {
+    fn map_corners<F, G>(&self, fo: F, fp: G) -> R2i
+        where
+            F: Fn(Vf) -> Vf,
+            G: Fn(Vf) -> Vf
+    {
+        let (o, p) = (self.origin, self.opp());
+        R2i::origin_opp(V2i(fo(o.0) as Vi, fo(o.1) as Vi), V2i(fp(p.0) as Vi, fp(p.1) as Vi))
+    }
+
+    /* Both corners floored. */
+    pub fn floor_to_i2(&self) -> R2i { self.map_corners(Vf::floor, Vf::floor) }
+    /* Both corners rounded to the nearest integer. */
+    pub fn round_to_i2(&self) -> R2i { self.map_corners(Vf::round, Vf::round) }
+    /* Origin floored and opp ceiled: the smallest R2i whose tiles cover all of self. */
+    pub fn ceil_to_i2(&self) -> R2i { self.map_corners(Vf::floor, Vf::ceil) }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct R2iIter {
     rect: R2i,
@@ -142,8 +392,8 @@ impl Iterator for R2iIter {
         let cur = self.current;
         let opp = self.rect.opp();
 
-        if cur.1 >= opp.1 {
-            return None;
+        if cur.1 >= opp.1 || cur.0 >= opp.0 {
+            return None;  // NB: The second test catches zero-width rects, which never wrap a row
         }
 
         self.current.0 += 1;
@@ -156,6 +406,33 @@ impl Iterator for R2iIter {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct R2iRevIter {
+    rect: R2i,
+    current: V2i,
+}
+
+impl Iterator for R2iRevIter {
+    type Item = V2i;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cur = self.current;
+        let orig = self.rect.origin();
+
+        if cur.1 < orig.1 || cur.0 < orig.0 {
+            return None;
+        }
+
+        self.current.0 -= 1;
+        if self.current.0 < orig.0 {
+            self.current.0 = self.rect.last().0;
+            self.current.1 -= 1;
+        }
+
+        Some(cur)
+    }
+}
+
 impl R2i {
     pub fn iter(&self) -> R2iIter {
         R2iIter {
@@ -163,6 +440,94 @@ impl R2i {
             current: self.origin,
         }
     }
+
+    /* The cells of iter() in reverse, from last() back to the origin. */
+    pub fn iter_rev(&self) -> R2iRevIter {
+        R2iRevIter {
+            rect: *self,
+            current: if self.dim.is_strict_q1() { self.last() } else { self.origin - V2i::ONE },
+        }
+    }
+
+    /* The inclusive far corner: the last cell iter() yields. Unlike opp(), it lies inside a
+     * non-empty rect. */
+    pub fn last(&self) -> V2i { self.opp() - V2i::ONE }
+
+    /* The contained tile nearest v, clamping against last() rather than opp(). An empty rect
+     * contains no tiles, so the result is then not contained either. */
+    pub fn clamp_point(&self, v: V2i) -> V2i { v.clamp(self.origin, self.last()) }
+
+    /* The outermost ring of cells in row-major order, each once; a rect one cell wide or tall is
+     * all border. */
+    pub fn border_iter(&self) -> impl Iterator<Item=V2i> {
+        let (first, last) = (self.origin, self.last());
+        (first.1..=last.1).flat_map(move |y| {
+            let step = if y == first.1 || y == last.1 { 1 } else { (last.0 - first.0).max(1) };
+            (first.0..=last.0).step_by(step as usize).map(move |x| V2i(x, y))
+        })
+    }
+
+    /* Cuts at the absolute column x = at into left and right halves, the column itself going
+     * right. at is clamped into [origin.0, opp.0], so one half may be empty. */
+    pub fn split_h(&self, at: Vi) -> (R2i, R2i) {
+        let at = at.max(self.origin.0).min(self.opp().0);
+        (
+            R2i::origin_opp(self.origin, V2i(at, self.opp().1)),
+            R2i::origin_opp(V2i(at, self.origin.1), self.opp()),
+        )
+    }
+
+    /* Cuts at the absolute row y = at into top and bottom halves, likewise clamped. */
+    pub fn split_v(&self, at: Vi) -> (R2i, R2i) {
+        let at = at.max(self.origin.1).min(self.opp().1);
+        (
+            R2i::origin_opp(self.origin, V2i(self.opp().0, at)),
+            R2i::origin_opp(V2i(self.origin.0, at), self.opp()),
+        )
+    }
+
+    /* counts.0 by counts.1 tiles in row-major order, exactly covering self; remainders go one
+     * extra row or column apiece to the tiles nearest the origin, so tiles are empty only when
+     * counts exceeds dim. Yields nothing if either count is non-positive. */
+    pub fn subdivide(&self, counts: V2i) -> impl Iterator<Item=R2i> {
+        let (origin, dim) = (self.origin, self.dim);
+        let counts = if counts.is_strict_q1() { counts } else { V2i::ZERO };
+        let edge = move |i: V2i| {
+            let (base, rem) = (dim / counts, dim.rem_euclid(counts));
+            origin + base * i + i.min(rem)
+        };
+        R2i::origin_dim(V2i::ZERO, counts).iter().map(move |i| R2i::origin_opp(edge(i), edge(i + V2i::ONE)))
+    }
+
+    /* Row-major from the origin; odd remainders go to the quadrants nearer the origin. */
+    pub fn quadrants(&self) -> [R2i; 4] {
+        let half = V2i((self.dim.0 + 1) / 2, (self.dim.1 + 1) / 2);
+        let (orig, mid, opp) = (self.origin, self.origin + half, self.opp());
+        [
+            R2i::origin_opp(orig, mid),
+            R2i::origin_opp(V2i(mid.0, orig.1), V2i(opp.0, mid.1)),
+            R2i::origin_opp(V2i(orig.0, mid.1), V2i(mid.0, opp.1)),
+            R2i::origin_opp(mid, opp),
+        ]
+    }
+
+    /* Up to four disjoint pieces covering self \ other: full-width top and bottom bands, then the
+     * left and right remnants beside the intersection. */
+    pub fn subtract(&self, other: R2i) -> Vec<R2i> {
+        let isct = match self.intersect(other) {
+            Some(isct) => isct,
+            None => return vec![*self],
+        };
+        let (orig, opp) = (self.origin, self.opp());
+        let (iorig, iopp) = (isct.origin, isct.opp());
+
+        [
+            R2i::origin_opp(orig, V2i(opp.0, iorig.1)),
+            R2i::origin_opp(V2i(orig.0, iopp.1), opp),
+            R2i::origin_opp(V2i(orig.0, iorig.1), V2i(iorig.0, iopp.1)),
+            R2i::origin_opp(V2i(iopp.0, iorig.1), V2i(opp.0, iopp.1)),
+        ].iter().copied().filter(|r| r.dim.is_strict_q1()).collect()
+    }
 }
 
 impl IntoIterator for &R2i {
@@ -175,7 +540,7 @@ impl IntoIterator for &R2i {
 }
 
 macro_rules! impl_binop {
-    ($trait:ident, $func:ident, $binop:tt) => {
+    ($trait:ident, $func:ident, $atrait:ident, $afunc:ident, $binop:tt) => {
         impl $trait for V2i {
             type Output = V2i;
             fn $func(self, rhs: V2i) -> V2i { V2i(self.0 $binop rhs.0, self.1 $binop rhs.1) }
@@ -185,18 +550,496 @@ macro_rules! impl_binop {
             type Output = V2f;
             fn $func(self, rhs: V2f) -> V2f { V2f(self.0 $binop rhs.0, self.1 $binop rhs.1) }
         }
+
+        impl $atrait for V2i {
+            fn $afunc(&mut self, rhs: V2i) { *self = *self $binop rhs; }
+        }
+
+        impl $atrait for V2f {
+            fn $afunc(&mut self, rhs: V2f) { *self = *self $binop rhs; }
+        }
+    }
+}
+
+impl_binop!(Add, add, AddAssign, add_assign, +);
+impl_binop!(Sub, sub, SubAssign, sub_assign, -);
+impl_binop!(Mul, mul, MulAssign, mul_assign, *);
+impl_binop!(Div, div, DivAssign, div_assign, /);
+
+macro_rules! impl_scalar_binop {
+    ($trait:ident, $func:ident, $atrait:ident, $afunc:ident, $binop:tt) => {
+        impl $trait<Vi> for V2i {
+            type Output = V2i;
+            fn $func(self, rhs: Vi) -> V2i { V2i(self.0 $binop rhs, self.1 $binop rhs) }
+        }
+
+        impl $trait<Vf> for V2f {
+            type Output = V2f;
+            fn $func(self, rhs: Vf) -> V2f { V2f(self.0 $binop rhs, self.1 $binop rhs) }
+        }
+
+        impl $atrait<Vi> for V2i {
+            fn $afunc(&mut self, rhs: Vi) { *self = *self $binop rhs; }
+        }
+
+        impl $atrait<Vf> for V2f {
+            fn $afunc(&mut self, rhs: Vf) { *self = *self $binop rhs; }
+        }
     }
 }
 
-impl_binop!(Add, add, +);
-impl_binop!(Sub, sub, -);
-impl_binop!(Mul, mul, *);
-impl_binop!(Div, div, /);
+impl_scalar_binop!(Mul, mul, MulAssign, mul_assign, *);
+impl_scalar_binop!(Div, div, DivAssign, div_assign, /);
+
+impl Mul<V2i> for Vi {
+    type Output = V2i;
+    fn mul(self, rhs: V2i) -> V2i { rhs * self }
+}
+
+impl Mul<V2f> for Vf {
+    type Output = V2f;
+    fn mul(self, rhs: V2f) -> V2f { rhs * self }
+}
+
+impl Neg for V2i {
+    type Output = V2i;
+    fn neg(self) -> V2i { V2i(-self.0, -self.1) }
+}
+
+impl Neg for V2f {
+    type Output = V2f;
+    fn neg(self) -> V2f { V2f(-self.0, -self.1) }
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn scalar_ops() {
+        assert_eq!(V2i(2, -3) * 3, V2i(6, -9));
+        assert_eq!(3 * V2i(2, -3), V2i(6, -9));
+        assert_eq!(V2i(7, -9) / 2, V2i(3, -4));
+        assert_eq!(V2f(1.0, -2.0) * 0.5, V2f(0.5, -1.0));
+        assert_eq!(0.5 * V2f(1.0, -2.0), V2f(0.5, -1.0));
+        assert_eq!(V2f(1.0, -2.0) / 4.0, V2f(0.25, -0.5));
+    }
+
+    #[test]
+    fn neg() {
+        assert_eq!(-V2i(2, -3), V2i(-2, 3));
+        assert_eq!(-V2i(0, 0), V2i(0, 0));
+        assert_eq!(-V2f(1.5, -0.5), V2f(-1.5, 0.5));
+        assert_eq!(V2i(4, 1) + -V2i(4, 1), V2i(0, 0));
+    }
+
+    #[test]
+    fn assign_ops() {
+        let mut p = V2i(1, 1);
+        p += V2i(2, 3);
+        assert_eq!(p, V2i(3, 4));
+        p -= V2i(1, 1);
+        assert_eq!(p, V2i(2, 3));
+        p *= V2i(3, 2);
+        assert_eq!(p, V2i(6, 6));
+        p /= V2i(2, 3);
+        assert_eq!(p, V2i(3, 2));
+        p *= 2;
+        assert_eq!(p, V2i(6, 4));
+        p /= 2;
+        assert_eq!(p, V2i(3, 2));
+
+        let mut f = V2f(1.0, 1.0);
+        f += V2f(2.0, 3.0);
+        assert_eq!(f, V2f(3.0, 4.0));
+        f -= V2f(1.0, 1.0);
+        assert_eq!(f, V2f(2.0, 3.0));
+        f *= V2f(3.0, 2.0);
+        assert_eq!(f, V2f(6.0, 6.0));
+        f /= V2f(2.0, 3.0);
+        assert_eq!(f, V2f(3.0, 2.0));
+        f *= 0.5;
+        assert_eq!(f, V2f(1.5, 1.0));
+        f /= 0.5;
+        assert_eq!(f, V2f(3.0, 2.0));
+    }
+
+    #[test]
+    fn rot() {
+        assert_eq!(V2i(1, 0).rot_cw(), V2i(0, 1));
+        assert_eq!(V2i(0, 1).rot_cw(), V2i(-1, 0));
+        assert_eq!(V2i(1, 0).rot_ccw(), V2i(0, -1));
+        for &v in &[V2i(3, -2), V2i(0, 5), V2i(-4, -1)] {
+            assert_eq!(v.rot_cw().rot_cw().rot_cw().rot_cw(), v);
+            assert_eq!(v.rot_ccw().rot_ccw().rot_ccw().rot_ccw(), v);
+            assert_eq!(v.rot_cw().rot_ccw(), v);
+            assert_eq!(v.rot_cw().rot_cw(), -v);
+            assert_eq!(v.rot_cw().l2_sq(), v.l2_sq());
+        }
+    }
+
+    #[test]
+    fn normalize() {
+        assert!((V2f(3.0, 4.0).normalize().l2() - 1.0).abs() < 1e-12);
+        assert_eq!(V2f(0.0, -2.0).normalize(), V2f(0.0, -1.0));
+        assert_eq!(V2f(0.0, 0.0).normalize(), V2f(0.0, 0.0));
+        let v = V2f(3.0, 4.0).with_length(10.0);
+        assert!((v.0 - 6.0).abs() < 1e-12 && (v.1 - 8.0).abs() < 1e-12);
+        assert_eq!(V2f(0.0, 0.0).with_length(5.0), V2f(0.0, 0.0));
+    }
+
+    #[test]
+    fn aspect_ratio() {
+        assert_eq!(R2i::origin_dim(V2i(3, -1), V2i(2, 8)).aspect_ratio(), 4.0);
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(8, 2)).aspect_ratio(), 4.0);
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(5, 5)).aspect_ratio(), 1.0);
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(0, 5)).aspect_ratio(), f64::INFINITY);
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(0, 0)).aspect_ratio(), f64::INFINITY);
+        assert_eq!(R2f::origin_dim(V2f(0.0, 0.0), V2f(1.5, 3.0)).aspect_ratio(), 2.0);
+    }
+
+    #[test]
+    fn from_angle() {
+        use std::f64::consts::PI;
+        for i in -15..16 {
+            let a = i as Vf * PI / 16.0;
+            assert!((V2f::from_angle(a).ang() - a).abs() < 1e-12, "angle {}", a);
+            assert!((V2f::from_angle(a).l2() - 1.0).abs() < 1e-12);
+            assert!((V2f::from_angle_len(a, 3.0).l2() - 3.0).abs() < 1e-12);
+        }
+        let v = V2f::from_angle_len(PI / 2.0, 2.0);
+        assert!(v.0.abs() < 1e-12 && (v.1 - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn lerp() {
+        let (a, b) = (V2f(1.0, -2.0), V2f(5.0, 6.0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.25), V2f(2.0, 0.0));
+        assert_eq!(a.lerp(b, -0.5), V2f(-1.0, -6.0));
+
+        let (a, b) = (V2i(0, 10), V2i(7, -3));
+        assert_eq!(a.lerp_round(b, 0.0), a);
+        assert_eq!(a.lerp_round(b, 1.0), b);
+        assert_eq!(a.lerp_round(b, 0.5), V2i(4, 4));  // NB: 3.5 and 3.5 round away from zero
+        assert_eq!(a.lerp_round(b, 0.3), V2i(2, 6));
+    }
+
+    #[test]
+    fn signum() {
+        assert_eq!(V2i(-4, 7).signum(), V2i(-1, 1));
+        assert_eq!(V2i(0, -3).signum(), V2i(0, -1));
+        assert_eq!(V2f(-0.5, 2.0).signum(), V2f(-1.0, 1.0));
+        assert_eq!(V2f(0.0, -0.0).signum(), V2f(0.0, 0.0));
+        assert_eq!(V2f(-0.0, 3.0).signum(), V2f(0.0, 1.0));
+        assert!(V2f(f64::NAN, 1.0).signum().0.is_nan());
+    }
+
+    #[test]
+    fn clamp() {
+        let (lo, hi) = (V2i(0, 0), V2i(9, 4));
+        assert_eq!(V2i(3, 2).clamp(lo, hi), V2i(3, 2));
+        assert_eq!(V2i(-3, 7).clamp(lo, hi), V2i(0, 4));
+        assert_eq!(V2i(12, -1).clamp(lo, hi), V2i(9, 0));
+        assert_eq!(V2i(5, 5).clamp(V2i(6, 0), V2i(2, 9)), V2i(2, 5));
+
+        assert_eq!(V2f(-1.0, 0.5).clamp(V2f(0.0, 0.0), V2f(1.0, 1.0)), V2f(0.0, 0.5));
+        assert_eq!(V2f(0.5, 0.5).clamp(V2f(1.0, 0.0), V2f(0.0, 1.0)), V2f(0.0, 0.5));
+    }
+
+    #[test]
+    fn tuple_conversions() {
+        assert_eq!(V2i::from((3, -4)), V2i(3, -4));
+        assert_eq!(V2i::from([3, -4]), V2i(3, -4));
+        assert_eq!(<(Vi, Vi)>::from(V2i(3, -4)), (3, -4));
+        assert_eq!(<[Vi; 2]>::from(V2i(3, -4)), [3, -4]);
+
+        let v: V2f = (0.5, 1.5).into();
+        assert_eq!(v, V2f(0.5, 1.5));
+        assert_eq!(V2f::from([0.5, 1.5]), v);
+        let t: (Vf, Vf) = v.into();
+        let a: [Vf; 2] = v.into();
+        assert_eq!((t, a), ((0.5, 1.5), [0.5, 1.5]));
+
+        assert_eq!(V2f::from(V2i(2, 3)), V2f(2.0, 3.0));
+    }
+
+    #[test]
+    fn index() {
+        let mut v = V2i(3, -4);
+        assert_eq!((v[0], v[1]), (3, -4));
+        for axis in 0..2 {
+            v[axis] += 1;
+        }
+        assert_eq!(v, V2i(4, -3));
+
+        let mut f = V2f(0.5, 1.5);
+        f[1] = 2.5;
+        assert_eq!((f[0], f[1]), (0.5, 2.5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_range() {
+        let _ = V2i(1, 2)[2];
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_mut_out_of_range() {
+        V2f(1.0, 2.0)[2] = 0.0;
+    }
+
+    #[test]
+    fn consts() {
+        assert_eq!(V2i::ZERO, V2i(0, 0));
+        assert_eq!(V2i::X + V2i::Y, V2i::ONE);
+        assert_eq!(V2f::X + V2f::Y, V2f::ONE);
+        assert_eq!(V2f::ONE - V2f::ONE, V2f::ZERO);
+        assert_eq!(V2i::X.rot_cw(), V2i::Y);
+        assert_eq!(R2i::origin_dim(V2i::ZERO, V2i::ONE).last(), V2i::ZERO);
+    }
+
+    #[test]
+    fn area_perimeter() {
+        let r = R2i::origin_dim(V2i(-2, 3), V2i(5, 5));
+        assert_eq!((r.area(), r.perimeter()), (25, 20));
+        let r = R2i::origin_dim(V2i(0, 0), V2i(3, 1));
+        assert_eq!((r.area(), r.perimeter()), (3, 8));
+        let r = R2i::origin_dim(V2i(0, 0), V2i(0, 4));
+        assert_eq!((r.area(), r.perimeter()), (0, 8));
+        let r = R2f::origin_dim(V2f(0.0, 0.0), V2f(1.5, 2.0));
+        assert_eq!((r.area(), r.perimeter()), (3.0, 7.0));
+    }
+
+    #[test]
+    fn direction_steps() {
+        for &d in &Direction::ALL {
+            assert_eq!(Direction::from_step(d.step()), Some(d));
+            assert_eq!(d.step().linf(), 1);
+        }
+        assert_eq!(Direction::N.step(), -V2i::Y);
+        assert_eq!(Direction::from_step(V2i(2, 0)), None);
+        assert_eq!(Direction::from_step(V2i::ZERO), None);
+    }
+
+    #[test]
+    fn contains_point_rect() {
+        let r = R2i::origin_dim(V2i(-1, 2), V2i(3, 4));
+        assert!(r.iter().all(|pt| r.contains_point(pt)));
+        assert!(r.contains_point(r.origin()));
+        assert!(r.contains_point(r.last()));
+        assert!(!r.contains_point(r.opp()));
+        assert!(!r.contains_point(V2i(2, 3)));
+        assert!(!r.contains_point(V2i(0, 6)));
+        assert!(!r.contains_point(V2i(-2, 3)));
+
+        assert!(r.contains_rect(r));
+        assert!(r.contains_rect(R2i::origin_dim(V2i(0, 3), V2i(2, 3))));
+        assert!(!r.contains_rect(R2i::origin_dim(V2i(0, 3), V2i(3, 3))));
+        assert!(!r.contains_rect(R2i::origin_dim(V2i(-2, 2), V2i(1, 1))));
+        assert!(r.contains_rect(R2i::origin_dim(r.opp(), V2i::ZERO)));
+
+        let f = R2f::origin_dim(V2f(0.0, 0.0), V2f(1.0, 1.0));
+        assert!(f.contains_point(V2f(0.5, 0.0)) && !f.contains_point(V2f(1.0, 0.5)));
+        assert!(f.contains_rect(R2f::origin_dim(V2f(0.25, 0.25), V2f(0.75, 0.75))));
+    }
+
+    #[test]
+    fn overlaps_matches_intersect() {
+        let base = R2i::origin_dim(V2i(0, 0), V2i(4, 3));
+        let others = [
+            R2i::origin_dim(V2i(1, 1), V2i(1, 1)),
+            R2i::origin_dim(V2i(3, 2), V2i(5, 5)),
+            R2i::origin_dim(V2i(4, 0), V2i(2, 2)),
+            R2i::origin_dim(V2i(0, 3), V2i(2, 2)),
+            R2i::origin_dim(V2i(-2, -2), V2i(2, 9)),
+            R2i::origin_dim(V2i(-1, -1), V2i(9, 9)),
+            R2i::origin_dim(V2i(2, 1), V2i(0, 4)),
+            R2i::origin_dim(V2i(10, 10), V2i(1, 1)),
+        ];
+        for &o in &others {
+            assert_eq!(base.overlaps(o), base.intersect(o).is_some(), "{:?}", o);
+            assert_eq!(o.overlaps(base), base.overlaps(o));
+        }
+        assert_eq!(others.iter().filter(|&&o| base.overlaps(o)).count(), 3);
+
+        let f = R2f::origin_dim(V2f(0.0, 0.0), V2f(1.0, 1.0));
+        assert!(f.overlaps(R2f::origin_dim(V2f(0.5, 0.5), V2f(1.0, 1.0))));
+        assert!(!f.overlaps(R2f::origin_dim(V2f(1.0, 0.0), V2f(1.0, 1.0))));
+    }
+
+    #[test]
+    fn border_iter() {
+        let r = R2i::origin_dim(V2i(-1, 2), V2i(4, 3));
+        let border: Vec<V2i> = r.border_iter().collect();
+        assert_eq!(border, vec![
+            V2i(-1, 2), V2i(0, 2), V2i(1, 2), V2i(2, 2),
+            V2i(-1, 3), V2i(2, 3),
+            V2i(-1, 4), V2i(0, 4), V2i(1, 4), V2i(2, 4),
+        ]);
+        assert_eq!(border, r.iter().filter(|&pt| pt.0 == -1 || pt.0 == 2 || pt.1 == 2 || pt.1 == 4).collect::<Vec<_>>());
+
+        let tall = R2i::origin_dim(V2i(0, 0), V2i(1, 4));
+        assert_eq!(tall.border_iter().collect::<Vec<_>>(), tall.iter().collect::<Vec<_>>());
+        let wide = R2i::origin_dim(V2i(0, 0), V2i(4, 1));
+        assert_eq!(wide.border_iter().collect::<Vec<_>>(), wide.iter().collect::<Vec<_>>());
+        let two = R2i::origin_dim(V2i(0, 0), V2i(2, 3));
+        assert_eq!(two.border_iter().count(), 6);
+        assert_eq!(R2i::origin_dim(V2i(5, 5), V2i::ONE).border_iter().collect::<Vec<_>>(), vec![V2i(5, 5)]);
+        assert_eq!(R2i::origin_dim(V2i(5, 5), V2i(0, 3)).border_iter().count(), 0);
+        assert_eq!(R2i::origin_dim(V2i(5, 5), V2i(3, 0)).border_iter().count(), 0);
+    }
+
+    #[test]
+    fn split() {
+        let r = R2i::origin_dim(V2i(-2, 1), V2i(6, 4));
+        let (l, rt) = r.split_h(1);
+        assert_eq!(l, R2i::origin_dim(V2i(-2, 1), V2i(3, 4)));
+        assert_eq!(rt, R2i::origin_dim(V2i(1, 1), V2i(3, 4)));
+        let (t, b) = r.split_v(4);
+        assert_eq!(t, R2i::origin_dim(V2i(-2, 1), V2i(6, 3)));
+        assert_eq!(b, R2i::origin_dim(V2i(-2, 4), V2i(6, 1)));
+
+        for &(a, b) in &[(l, rt), (t, b)] {
+            assert_eq!(a.union(b), r);
+            assert!(!a.overlaps(b));
+            assert_eq!(a.area() + b.area(), r.area());
+        }
+
+        let (l, rt) = r.split_h(-10);
+        assert_eq!((l.area(), rt), (0, r));
+        let (t, b) = r.split_v(99);
+        assert_eq!((t, b.area()), (r, 0));
+    }
+
+    #[test]
+    fn subdivide() {
+        for &(r, counts) in &[
+            (R2i::origin_dim(V2i(-3, 2), V2i(10, 7)), V2i(3, 2)),
+            (R2i::origin_dim(V2i(0, 0), V2i(8, 8)), V2i(4, 4)),
+            (R2i::origin_dim(V2i(1, 1), V2i(5, 2)), V2i(1, 3)),
+        ] {
+            let tiles: Vec<R2i> = r.subdivide(counts).collect();
+            assert_eq!(tiles.len() as Vi, counts.0 * counts.1);
+            assert_eq!(tiles.iter().map(R2i::area).sum::<Vi>(), r.area());
+            let mut cells: Vec<V2i> = tiles.iter().flat_map(R2i::iter).collect();
+            cells.sort();
+            let mut expected: Vec<V2i> = r.iter().collect();
+            expected.sort();
+            assert_eq!(cells, expected);
+        }
+
+        let tiles: Vec<R2i> = R2i::origin_dim(V2i(0, 0), V2i(10, 1)).subdivide(V2i(3, 1)).collect();
+        assert_eq!(tiles.iter().map(|t| t.dim().0).collect::<Vec<_>>(), vec![4, 3, 3]);
+        assert_eq!(tiles[1].origin(), V2i(4, 0));
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(4, 4)).subdivide(V2i(0, 2)).count(), 0);
+    }
+
+    #[test]
+    fn r2f_to_i2() {
+        let r = R2f::origin_opp(V2f(1.2, -0.5), V2f(3.8, 2.0));
+        let cover = r.ceil_to_i2();
+        assert_eq!(cover, R2i::origin_opp(V2i(1, -1), V2i(4, 2)));
+        assert_eq!((cover.origin().0, cover.last().0), (1, 3));
+        assert_eq!(r.floor_to_i2(), R2i::origin_opp(V2i(1, -1), V2i(3, 2)));
+        assert_eq!(r.round_to_i2(), R2i::origin_opp(V2i(1, -1), V2i(4, 2)));
+
+        let exact = R2f::origin_dim(V2f(2.0, 3.0), V2f(4.0, 1.0));
+        let expected = R2i::origin_dim(V2i(2, 3), V2i(4, 1));
+        assert_eq!((exact.floor_to_i2(), exact.round_to_i2(), exact.ceil_to_i2()), (expected, expected, expected));
+    }
+
+    #[test]
+    fn clamp_point() {
+        let r = R2i::origin_dim(V2i(0, 0), V2i(10, 10));
+        assert_eq!(r.clamp_point(V2i(100, -5)), V2i(9, 0));
+        assert_eq!(r.clamp_point(V2i(3, 4)), V2i(3, 4));
+        assert_eq!(r.clamp_point(V2i(-1, 10)), V2i(0, 9));
+        for &v in &[V2i(100, -5), V2i(-7, 3), V2i(10, 10), V2i(5, 5)] {
+            assert!(r.contains_point(r.clamp_point(v)));
+        }
+    }
+
+    #[test]
+    fn rect_scalar_ops() {
+        let r = R2i::origin_dim(V2i(-2, 3), V2i(4, 3));
+        assert_eq!(r * 2, R2i::origin_dim(V2i(-2, 3), V2i(8, 6)));
+        assert_eq!(r * 2, r.scale(V2i(2, 2)));
+        assert_eq!(r / 2, R2i::origin_dim(V2i(-2, 3), V2i(2, 1)));
+        assert_eq!((r * 3) / 3, r);
+
+        let f = R2f::origin_dim(V2f(0.5, 0.5), V2f(2.0, 1.0));
+        assert_eq!(f * 0.5, R2f::origin_dim(V2f(0.5, 0.5), V2f(1.0, 0.5)));
+        assert_eq!(f / 4.0, R2f::origin_dim(V2f(0.5, 0.5), V2f(0.5, 0.25)));
+    }
+
+    #[test]
+    fn intersection_area() {
+        let r = R2i::origin_dim(V2i(0, 0), V2i(5, 4));
+        assert_eq!(r.intersection_area(R2i::origin_dim(V2i(3, 2), V2i(5, 5))), 4);
+        assert_eq!(r.intersection_area(r), 20);
+        assert_eq!(r.intersection_area(R2i::origin_dim(V2i(1, 1), V2i(1, 1))), 1);
+
+        let touching = R2i::origin_dim(V2i(5, 0), V2i(2, 4));
+        assert!(!r.overlaps(touching));
+        assert_eq!(r.intersection_area(touching), 0);
+        assert_eq!(r.intersection_area(R2i::origin_dim(V2i(9, 9), V2i(1, 1))), 0);
+
+        let f = R2f::origin_dim(V2f(0.0, 0.0), V2f(1.0, 1.0));
+        assert_eq!(f.intersection_area(R2f::origin_dim(V2f(0.5, 0.5), V2f(1.0, 1.0))), 0.25);
+    }
+
+    #[test]
+    fn linf_is_max() {
+        assert_eq!(V2i(3, 1).linf(), 3);
+        assert_eq!(V2i(-5, 2).linf(), 5);
+        assert_eq!(V2i(1, -4).linf(), 4);
+        assert_eq!(V2f(3.0, 1.0).linf(), 3.0);
+        assert_eq!(V2f(-5.0, 2.0).linf(), 5.0);
+    }
+
+    #[test]
+    fn dot_cross() {
+        assert_eq!(V2i(2, 3).dot(V2i(4, -1)), 5);
+        assert_eq!(V2i(2, 3).cross(V2i(4, -1)), -14);
+        assert_eq!(V2i(1, 0).cross(V2i(0, 1)), 1);
+        assert_eq!(V2i(3, 3).cross(V2i(1, 1)), 0);
+        assert_eq!(V2f(0.5, 2.0).dot(V2f(2.0, 0.25)), 1.5);
+        assert_eq!(V2f(1.0, 0.0).cross(V2f(0.0, -2.0)), -2.0);
+    }
+
+    #[test]
+    fn neighbor_arrays() {
+        assert_eq!(V2i(0, 0).neighbors4(), [V2i(1, 0), V2i(0, 1), V2i(-1, 0), V2i(0, -1)]);
+        let n8 = V2i(2, -3).neighbors8();
+        for n in &V2i(2, -3).neighbors4() {
+            assert!(n8.contains(n));
+        }
+        assert!(n8.iter().all(|&n| (n - V2i(2, -3)).linf() == 1));
+        assert_eq!(n8[1], V2i(3, -2));
+    }
+
+    #[test]
+    fn componentwise_pow() {
+        assert_eq!(V2f(4.0, 9.0).sqrt(), V2f(2.0, 3.0));
+        assert_eq!(V2f(2.0, 3.0).powf(2.0), V2f(4.0, 9.0));
+        assert!((V2f(8.0, 27.0).powf(1.0 / 3.0) - V2f(2.0, 3.0)).linf() < 1e-12);
+    }
+
+    #[test]
+    fn angle_to() {
+        use std::f64::consts::PI;
+        let x = V2f(1.0, 0.0);
+        assert!((x.angle_to(V2f(0.0, 1.0)) - PI / 2.0).abs() < 1e-9);
+        assert!((x.angle_to(V2f(0.0, -1.0)) + PI / 2.0).abs() < 1e-9);
+        assert!((x.angle_to(V2f(-2.0, 0.0)) - PI).abs() < 1e-9);
+        assert_eq!(V2f(-1.0, 0.0).angle_to(V2f(1.0, 0.0)), PI);
+        assert_eq!(V2f(0.0, -1.0).angle_to(V2f(0.0, 1.0)), PI);
+        assert!((V2f(-1.0, -0.1).angle_to(V2f(-1.0, 0.1)) + 0.2f64.atan2(0.99)).abs() < 1e-9);
+    }
+
     #[test]
     fn rect_iter() {
         let r = R2i::origin_dim(V2i(0, 0), V2i(5, 5));
@@ -205,6 +1048,41 @@ mod test {
         assert_eq!(v.len(), 25);
     }
 
+    #[test]
+    fn rect_iter_empty() {
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(0, 3)).iter().count(), 0);
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(3, 0)).iter().count(), 0);
+        assert_eq!(R2i::origin_dim(V2i(-2, 5), V2i(0, 1)).iter().next(), None);
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(0, 3)).iter_rev().count(), 0);
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(3, 0)).iter_rev().count(), 0);
+    }
+
+    #[test]
+    fn rect_iter_rev() {
+        for &r in &[
+            R2i::origin_dim(V2i(0, 0), V2i(5, 5)),
+            R2i::origin_dim(V2i(-3, 2), V2i(4, 1)),
+            R2i::origin_dim(V2i(2, -2), V2i(1, 3)),
+        ] {
+            let mut fwd: Vec<_> = r.iter().collect();
+            fwd.reverse();
+            assert_eq!(r.iter_rev().collect::<Vec<_>>(), fwd);
+        }
+    }
+
+    #[test]
+    fn rect_last() {
+        for &r in &[
+            R2i::origin_dim(V2i(0, 0), V2i(5, 5)),
+            R2i::origin_dim(V2i(-3, 2), V2i(1, 4)),
+            R2i::origin_dim(V2i(7, -7), V2i(3, 1)),
+        ] {
+            assert_eq!(r.iter().last(), Some(r.last()));
+            assert!(r.contains(r.last()));
+            assert!(!r.contains(r.opp()));
+        }
+    }
+
     #[test]
     fn rect_isct() {
         let ra = R2i::origin_dim(V2i(0, 0), V2i(5, 5));
@@ -221,6 +1099,19 @@ mod test {
         let rb = R2i::origin_dim(V2i(5, 5), V2i(5, 5));
 
         assert!(ra.intersect(rb).is_none());
+        assert_eq!(ra.intersect_inclusive(rb), Some(R2i::origin_dim(V2i(5, 5), V2i(0, 0))));
+    }
+
+    #[test]
+    fn rect_isct_inclusive() {
+        let ra = R2i::origin_dim(V2i(0, 0), V2i(5, 5));
+        let rb = R2i::origin_dim(V2i(5, 2), V2i(3, 5));
+        assert!(ra.intersect(rb).is_none());
+        assert_eq!(ra.intersect_inclusive(rb), Some(R2i::origin_dim(V2i(5, 2), V2i(0, 3))));
+
+        let rc = R2i::origin_dim(V2i(3, 3), V2i(5, 5));
+        assert_eq!(ra.intersect_inclusive(rc), ra.intersect(rc));
+        assert!(ra.intersect_inclusive(R2i::origin_dim(V2i(6, 0), V2i(2, 2))).is_none());
     }
 
     #[test]
@@ -240,4 +1131,55 @@ mod test {
         println!("{:?}", un);
         assert_eq!(un.dim(), V2i(8, 8));
     }
+
+    #[test]
+    fn rect_quadrants() {
+        for &r in &[
+            R2i::origin_dim(V2i(0, 0), V2i(8, 8)),
+            R2i::origin_dim(V2i(-3, 2), V2i(5, 7)),
+            R2i::origin_dim(V2i(1, 1), V2i(1, 3)),
+        ] {
+            let quads = r.quadrants();
+            println!("{:?}: {:?}", r, quads);
+            let area = |q: &R2i| q.dim().0 * q.dim().1;
+            assert_eq!(quads.iter().map(area).sum::<Vi>(), area(&r));
+            for (i, a) in quads.iter().enumerate().filter(|(_, q)| area(q) > 0) {
+                assert_eq!(r.intersect(*a), Some(*a));
+                for b in &quads[i + 1..] {
+                    assert_eq!(a.intersect(*b), None);
+                }
+            }
+            assert!(quads[0].dim().0 >= quads[1].dim().0 && quads[0].dim().1 >= quads[2].dim().1);
+        }
+        assert_eq!(R2i::origin_dim(V2i(0, 0), V2i(5, 5)).quadrants()[0].dim(), V2i(3, 3));
+    }
+
+    #[test]
+    fn rect_subtract() {
+        let area = |r: R2i| r.dim().0 * r.dim().1;
+        let ra = R2i::origin_dim(V2i(0, 0), V2i(8, 8));
+        for &rb in &[
+            R2i::origin_dim(V2i(3, 3), V2i(2, 2)),
+            R2i::origin_dim(V2i(-2, 5), V2i(5, 5)),
+            R2i::origin_dim(V2i(2, -1), V2i(4, 20)),
+        ] {
+            let pieces = ra.subtract(rb);
+            println!("{:?} - {:?} = {:?}", ra, rb, pieces);
+            for (i, a) in pieces.iter().enumerate() {
+                assert!(rb.intersect(*a).is_none());
+                for b in &pieces[i + 1..] {
+                    assert!(a.intersect(*b).is_none());
+                }
+            }
+            let isct = ra.intersect(rb).expect("No intersection");
+            assert_eq!(pieces.iter().map(|&r| area(r)).sum::<Vi>(), area(ra) - area(isct));
+        }
+    }
+
+    #[test]
+    fn rect_subtract_extremes() {
+        let ra = R2i::origin_dim(V2i(0, 0), V2i(5, 5));
+        assert_eq!(ra.subtract(R2i::origin_dim(V2i(8, 8), V2i(5, 5))), vec![ra]);
+        assert!(ra.subtract(ra.grow(1)).is_empty());
+    }
 }
@@ -0,0 +1,260 @@
+use crate::*;
+use super::{Grid, path::{Neighbors, Traversable}};
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Clone)]
+pub struct Connectivity {
+    roots: Grid<Option<usize>>,
+}
+
+impl Connectivity {
+    pub fn component(&self, v: V2i) -> Option<usize> {
+        self.roots.get(v).ok().and_then(|r| *r)
+    }
+
+    pub fn connected(&self, a: V2i, b: V2i) -> bool {
+        match (self.component(a), self.component(b)) {
+            (Some(ra), Some(rb)) => ra == rb,
+            _ => false,
+        }
+    }
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];  // NB: Path halving
+        i = parent[i];
+    }
+    i
+}
+
+fn components(conn: &Connectivity) -> Vec<Vec<V2i>> {
+    let mut ids = HashMap::new();
+    let mut comps: Vec<Vec<V2i>> = Vec::new();
+    for pt in conn.roots.rect().iter() {
+        if let Some(root) = conn.component(pt) {
+            let id = *ids.entry(root).or_insert_with(|| {
+                comps.push(Vec::new());
+                comps.len() - 1
+            });
+            comps[id].push(pt);
+        }
+    }
+    comps
+}
+
+impl<T: Traversable> Grid<T> {
+    pub fn connectivity<N>(&self) -> Connectivity
+        where
+            V2i: Neighbors<N>
+    {
+        let mut parent: Vec<usize> = (0..self.array.len()).collect();
+        let mut neighbors = Vec::new();
+
+        for (i, pt) in self.rect().iter().enumerate() {
+            if !self.array[i].can_pass() {
+                continue;
+            }
+            pt.neighbors(&mut neighbors);
+            for neigh in neighbors.drain(..) {
+                if let Ok(j) = self.index_of(neigh) {
+                    if self.array[j].can_pass() {
+                        let (ri, rj) = (find(&mut parent, i), find(&mut parent, j));
+                        parent[ri.max(rj)] = ri.min(rj);
+                    }
+                }
+            }
+        }
+
+        let roots = (0..parent.len()).map(|i|
+            if self.array[i].can_pass() { Some(find(&mut parent, i)) } else { None }
+        ).collect();
+        Connectivity {
+            roots: Grid::from_vec(roots, self.origin, self.dim).unwrap(),
+        }
+    }
+}
+
+impl<T: Traversable + Clone> Grid<T> {
+    /* Walls off every component but the largest; ties keep the one found first in row-major order. */
+    pub fn keep_largest_component<N>(&mut self, wall: T)
+        where
+            V2i: Neighbors<N>
+    {
+        let mut comps = components(&self.connectivity::<N>());
+        let largest = comps.iter().enumerate()
+            .max_by_key(|&(i, c)| (c.len(), Reverse(i)))
+            .map(|(i, _)| i);
+        if let Some(largest) = largest {
+            comps.swap_remove(largest);
+        }
+        for pt in comps.into_iter().flatten() {
+            self.set(pt, wall.clone()).unwrap();
+        }
+    }
+
+    /* Joins every component with a minimum spanning tree of corridors, each edge weighted by the
+     * L1 distance between the closest cells of its two components (as found where their L1
+     * Voronoi regions meet, in time linear in the grid's area). Corridors run horizontally then
+     * vertically, so they connect under any topology. */
+    pub fn connect_regions<N>(&mut self, passable: T)
+        where
+            V2i: Neighbors<N>
+    {
+        let comps = components(&self.connectivity::<N>());
+
+        /* Breadth-first from every component at once, labeling each cell with the component (and
+         * the cell of it) that reached it first. Only where two labels meet can a candidate edge
+         * lie, and those candidates include every edge of the spanning tree. */
+        let mut nearest: Grid<Option<(usize, V2i)>> = Grid::from_default(self.origin, self.dim).unwrap();
+        let mut queue = VecDeque::new();
+        for (i, comp) in comps.iter().enumerate() {
+            for &pt in comp {
+                *nearest.get_mut(pt).unwrap() = Some((i, pt));
+                queue.push_back(pt);
+            }
+        }
+
+        let mut closest: HashMap<(usize, usize), (V2i, V2i)> = HashMap::new();
+        while let Some(pt) = queue.pop_front() {
+            let (i, src) = nearest.get(pt).unwrap().unwrap();
+            for &neigh in pt.neighbors4().iter() {
+                let slot = match nearest.get_mut(neigh) {
+                    Ok(slot) => slot,
+                    Err(_) => continue,
+                };
+                match *slot {
+                    None => {
+                        *slot = Some((i, src));
+                        queue.push_back(neigh);
+                    },
+                    Some((j, other)) if j != i => {
+                        let (key, pair) = if i < j { ((i, j), (src, other)) } else { ((j, i), (other, src)) };
+                        let len = (pair.0 - pair.1).l1();
+                        let best = closest.entry(key).or_insert(pair);
+                        if len < (best.0 - best.1).l1() {
+                            *best = pair;
+                        }
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        let mut edges: Vec<_> = closest.into_iter().map(|((i, j), (a, b))| ((a - b).l1(), i, j, a, b)).collect();
+        edges.sort_by_key(|e| (e.0, e.1, e.2));
+
+        let mut parent: Vec<usize> = (0..comps.len()).collect();
+        for (_, i, j, a, b) in edges {
+            let (ri, rj) = (find(&mut parent, i), find(&mut parent, j));
+            if ri == rj {
+                continue;
+            }
+            parent[ri.max(rj)] = ri.min(rj);
+            let corner = V2i(b.0, a.1);
+            self.draw_line(a, corner, passable.clone());
+            self.draw_line(corner, b, passable.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::path::{L1, Linf};
+
+    fn testing_grid() -> Grid<isize> {
+        Grid::from_vec(
+            vec![
+                0, 0, 1, 0, 0,
+                0, 0, 1, 0, 0,
+                1, 1, 1, 1, 1,
+                0, 1, 0, 0, 0,
+                1, 0, 1, 0, 1,
+            ], V2i(0, 0), V2i(5, 5),
+        ).expect("Creating the test grid failed")
+    }
+
+    #[test]
+    fn connected_l1() {
+        let conn = testing_grid().connectivity::<L1>();
+        println!("{:?}", conn);
+        assert!(conn.connected(V2i(0, 0), V2i(1, 1)));
+        assert!(conn.connected(V2i(3, 0), V2i(4, 1)));
+        assert!(conn.connected(V2i(2, 3), V2i(3, 4)));
+        assert!(!conn.connected(V2i(0, 0), V2i(3, 0)));
+        assert!(!conn.connected(V2i(0, 3), V2i(1, 4)));
+        assert!(!conn.connected(V2i(1, 4), V2i(2, 3)));
+        assert!(!conn.connected(V2i(2, 0), V2i(2, 0)));
+        assert!(!conn.connected(V2i(0, 0), V2i(-1, 0)));
+    }
+
+    #[test]
+    fn connected_linf() {
+        let conn = testing_grid().connectivity::<Linf>();
+        assert!(conn.connected(V2i(0, 3), V2i(1, 4)));
+        assert!(conn.connected(V2i(1, 4), V2i(2, 3)));
+        assert!(!conn.connected(V2i(0, 0), V2i(3, 0)));
+    }
+
+    #[test]
+    fn connect_regions() {
+        let mut grid: Grid<isize> = Grid::from_vec(
+            vec![
+                0, 0, 1, 1, 1, 1, 1,
+                0, 0, 1, 1, 1, 1, 1,
+                1, 1, 1, 1, 1, 0, 0,
+                1, 1, 1, 1, 1, 0, 0,
+                0, 1, 1, 1, 1, 1, 1,
+            ], V2i(0, 0), V2i(7, 5),
+        ).expect("Creating the test grid failed");
+        assert!(!grid.connectivity::<L1>().connected(V2i(0, 0), V2i(6, 3)));
+
+        grid.connect_regions::<L1>(0);
+        println!("{:?}", grid);
+        let conn = grid.connectivity::<L1>();
+        let open: Vec<_> = grid.rect().iter().filter(|&pt| grid.get(pt).unwrap().can_pass()).collect();
+        for &pt in &open {
+            assert!(conn.connected(open[0], pt));
+        }
+        assert_eq!(open.len(), 15);
+    }
+
+    #[test]
+    fn connect_many_regions() {
+        let mut grid: Grid<isize> = Grid::from_default(V2i(-4, -4), V2i(17, 13)).unwrap();
+        for pt in grid.rect().iter() {
+            if pt.0 % 3 != 0 || pt.1 % 4 != 0 {
+                grid.set(pt, 1).unwrap();
+            }
+        }
+        assert_eq!(components(&grid.connectivity::<L1>()).len(), 24);
+
+        grid.connect_regions::<L1>(0);
+        let conn = grid.connectivity::<L1>();
+        let open: Vec<_> = grid.rect().iter().filter(|&pt| grid.get(pt).unwrap().can_pass()).collect();
+        for &pt in &open {
+            assert!(conn.connected(open[0], pt));
+        }
+        /* The 20 corridors along the rows carve two cells each; the 3 joining the rows carve three. */
+        assert_eq!(open.len(), 24 + 20 * 2 + 3 * 3);
+    }
+
+    #[test]
+    fn keep_largest_component() {
+        let mut grid = testing_grid();
+        *grid.get_mut(V2i(0, 0)).unwrap() = 1;
+        *grid.get_mut(V2i(3, 0)).unwrap() = 1;
+        grid.keep_largest_component::<L1>(1);
+        println!("{:?}", grid);
+        let open: Vec<_> = grid.rect().iter().filter(|&pt| grid.get(pt).unwrap().can_pass()).collect();
+        assert_eq!(open, vec![V2i(2, 3), V2i(3, 3), V2i(4, 3), V2i(3, 4)]);
+
+        let mut grid = testing_grid();
+        grid.keep_largest_component::<L1>(1);
+        assert_eq!(grid.iter().filter(|t| t.can_pass()).count(), 4);
+        assert!(grid.get(V2i(0, 0)).unwrap().can_pass());
+    }
+}
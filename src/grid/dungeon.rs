@@ -0,0 +1,138 @@
+use crate::*;
+use super::Grid;
+
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DungeonParams {
+    pub rooms: usize,
+    pub attempts: usize,
+    pub room_min: V2i,
+    pub room_max: V2i,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Grid(super::Error),
+    /* (room_min, room_max) of DungeonParams, when empty or not at least one cell on each axis */
+    BadRoomSize(V2i, V2i),
+}
+
+impl From<super::Error> for Error {
+    fn from(e: super::Error) -> Error { Error::Grid(e) }
+}
+
+impl Default for DungeonParams {
+    fn default() -> DungeonParams {
+        DungeonParams {
+            rooms: 8,
+            attempts: 200,
+            room_min: V2i(3, 3),
+            room_max: V2i(8, 6),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /* Builds a grid with origin (0, 0) and the given dim, all wall but for up to params.rooms
+     * rooms placed randomly inside its one-cell border, rejecting any that would touch an earlier
+     * one; each room is joined to the previous with an L-shaped corridor between their centers.
+     * Every floor cell is 4-connected to every other. Returns a Result rather than a bare Grid so
+     * bad input is reported: a negative dim, or a room_min not at least (1, 1) or exceeding
+     * room_max on either axis. */
+    pub fn generate_dungeon<R: Rng>(dim: V2i, rng: &mut R, params: DungeonParams, wall: T, floor: T) -> Result<Grid<T>, Error> {
+        let (min, max) = (params.room_min, params.room_max);
+        if !min.is_strict_q1() || !(max - min).is_q1() {
+            return Err(Error::BadRoomSize(min, max));
+        }
+        let mut grid = Grid::from_generator(|_| wall.clone(), V2i::ZERO, dim)?;
+        if !(dim - V2i::ONE * 2).is_strict_q1() {
+            return Ok(grid);  // NB: No interior to put a room in
+        }
+        let interior = R2i::origin_dim(V2i::ONE, dim - V2i::ONE * 2);
+        let mut rooms: Vec<R2i> = Vec::new();
+
+        for _ in 0..params.attempts {
+            if rooms.len() >= params.rooms {
+                break;
+            }
+            let size = V2i(
+                rng.gen_range(min.0..=max.0),
+                rng.gen_range(min.1..=max.1),
+            );
+            let span = interior.dim() - size;
+            if !span.is_q1() {
+                continue;
+            }
+            let room = R2i::origin_dim(
+                interior.origin() + V2i(rng.gen_range(0..=span.0), rng.gen_range(0..=span.1)),
+                size,
+            );
+            let halo = R2i::origin_opp(room.origin() - V2i::ONE, room.opp() + V2i::ONE);
+            if rooms.iter().any(|r| r.intersect(halo).is_some()) {
+                continue;
+            }
+
+            for pt in room.iter() {
+                grid.set(pt, floor.clone()).unwrap();
+            }
+            if let Some(prev) = rooms.last() {
+                let (a, b) = (prev.origin() + prev.dim() / V2i(2, 2), room.origin() + room.dim() / V2i(2, 2));
+                let corner = if rng.gen() { V2i(b.0, a.1) } else { V2i(a.0, b.1) };
+                grid.draw_line(a, corner, floor.clone());
+                grid.draw_line(corner, b, floor.clone());
+            }
+            rooms.push(room);
+        }
+
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::path::{L1, Traversable};
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn dungeon_connected() {
+        for seed in 0..8 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let grid = Grid::generate_dungeon(V2i(48, 32), &mut rng, DungeonParams::default(), 1isize, 0).unwrap();
+            let floors: Vec<_> = grid.rect().iter().filter(|&pt| grid.get(pt).unwrap().can_pass()).collect();
+            assert!(floors.len() > 9);
+            for pt in grid.rect().iter().filter(|pt| pt.0 == 0 || pt.1 == 0 || pt.0 == 47 || pt.1 == 31) {
+                assert!(!grid.get(pt).unwrap().can_pass());
+            }
+
+            let conn = grid.connectivity::<L1>();
+            for _ in 0..16 {
+                let a = floors[rng.gen_range(0..floors.len())];
+                let b = floors[rng.gen_range(0..floors.len())];
+                assert!(conn.connected(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn dungeon_rejects_bad_params() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let bad = |room_min, room_max| DungeonParams { room_min, room_max, ..Default::default() };
+        assert!(matches!(
+            Grid::generate_dungeon(V2i(-4, 8), &mut rng, DungeonParams::default(), 1isize, 0),
+            Err(Error::Grid(crate::grid::Error::NegativeDim(_))),
+        ));
+        for &(lo, hi) in &[(V2i(4, 3), V2i(3, 6)), (V2i(3, 7), V2i(8, 6)), (V2i(0, 3), V2i(8, 6)), (V2i(-2, -2), V2i(1, 1))] {
+            assert!(matches!(
+                Grid::generate_dungeon(V2i(48, 32), &mut rng, bad(lo, hi), 1isize, 0),
+                Err(Error::BadRoomSize(a, b)) if a == lo && b == hi,
+            ));
+        }
+
+        /* Too small for any room is not an error, just all wall. */
+        let grid = Grid::generate_dungeon(V2i(2, 9), &mut rng, DungeonParams::default(), 1isize, 0).unwrap();
+        assert!(grid.rect().iter().all(|pt| *grid.get(pt).unwrap() == 1));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::*;
+use super::{Error, Grid};
+
+/* Undo history over a borrowed Grid, keeping only (point, old, new) per edit rather than
+ * snapshots. Making a new edit discards anything that could have been redone. */
+#[derive(Debug)]
+pub struct EditLog<'a, T> {
+    grid: &'a mut Grid<T>,
+    done: Vec<(V2i, T, T)>,
+    undone: Vec<(V2i, T, T)>,
+}
+
+impl<'a, T: Clone> EditLog<'a, T> {
+    pub fn new(grid: &'a mut Grid<T>) -> EditLog<'a, T> {
+        EditLog { grid, done: Vec::new(), undone: Vec::new() }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        self.grid
+    }
+
+    /* Nothing is recorded if v is out of bounds. */
+    pub fn set(&mut self, v: V2i, value: T) -> Result<(), Error> {
+        let tile = self.grid.get_mut(v)?;
+        let old = std::mem::replace(tile, value.clone());
+        self.done.push((v, old, value));
+        self.undone.clear();
+        Ok(())
+    }
+
+    /* Returns false if there was nothing to undo. */
+    pub fn undo(&mut self) -> bool {
+        match self.done.pop() {
+            Some(edit) => {
+                *self.grid.get_mut(edit.0).unwrap() = edit.1.clone();
+                self.undone.push(edit);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /* Returns false if there was nothing to redo. */
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(edit) => {
+                *self.grid.get_mut(edit.0).unwrap() = edit.2.clone();
+                self.done.push(edit);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool { !self.done.is_empty() }
+    pub fn can_redo(&self) -> bool { !self.undone.is_empty() }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn testing_grid() -> Grid<isize> {
+        Grid::from_generator(|pt| pt.0 + 10 * pt.1, V2i(0, 0), V2i(3, 3)).expect("Creating the test grid failed")
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut grid = testing_grid();
+        let original = testing_grid().into_vec();
+        {
+            let mut log = EditLog::new(&mut grid);
+            log.set(V2i(1, 1), -1).unwrap();
+            log.set(V2i(2, 0), -2).unwrap();
+            log.set(V2i(1, 1), -3).unwrap();
+            assert!(log.set(V2i(3, 0), -4).is_err());
+            assert_eq!(*log.grid().get(V2i(1, 1)).unwrap(), -3);
+
+            assert!(log.undo());
+            assert_eq!(*log.grid().get(V2i(1, 1)).unwrap(), -1);
+            assert!(log.undo() && log.undo());
+            assert!(!log.undo());
+            assert_eq!(log.grid().clone().into_vec(), original);
+
+            assert!(log.redo());
+            assert_eq!(*log.grid().get(V2i(1, 1)).unwrap(), -1);
+            log.set(V2i(0, 2), 7).unwrap();
+            assert!(!log.can_redo());
+            assert!(!log.redo());
+        }
+        assert_eq!(*grid.get(V2i(1, 1)).unwrap(), -1);
+        assert_eq!(*grid.get(V2i(0, 2)).unwrap(), 7);
+        assert_eq!(*grid.get(V2i(2, 0)).unwrap(), 2);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::*;
+use super::{Grid, path::Neighbors};
+
+/* The four cells around p with their bilinear weights, which sum to 1. */
+fn bilinear_corners(p: V2f) -> [(V2i, f64); 4] {
+    let cell = V2f(p.0.floor(), p.1.floor());
+    let frac = p - cell;
+    let base = V2i::from(cell);
+    [
+        (base, (1.0 - frac.0) * (1.0 - frac.1)),
+        (base + V2i::X, frac.0 * (1.0 - frac.1)),
+        (base + V2i::Y, (1.0 - frac.0) * frac.1),
+        (base + V2i::ONE, frac.0 * frac.1),
+    ]
+}
+
+impl Grid<f64> {
+    /* Treats cell values as samples at integer coordinates. Corners with zero weight needn't be in
+     * bounds, so sampling exactly on the last row or column still works. */
+    pub fn sample_bilinear(&self, p: V2f) -> Option<f64> {
+        let mut acc = 0.0;
+        for &(pt, w) in &bilinear_corners(p) {
+            if w > 0.0 {
+                acc += w * self.get(pt).ok()?;
+            }
+        }
+        Some(acc)
+    }
+
+    /* The adjoint of sample_bilinear: adds amount to the four cells around p by the same weights.
+     * Shares landing out of bounds are dropped, not renormalized, so total mass is conserved only
+     * away from the edges. */
+    pub fn splat(&mut self, p: V2f, amount: f64) {
+        for &(pt, w) in &bilinear_corners(p) {
+            if let Some(cell) = self.try_get_mut(pt) {
+                *cell += w * amount;
+            }
+        }
+    }
+
+    /* Mean over the in-bounds N-neighbors of p (and p itself if asked and in bounds); 0.0 if
+     * there are none. */
+    pub fn neighbor_average<N>(&self, p: V2i, include_self: bool) -> f64
+        where
+            V2i: Neighbors<N>
+    {
+        let mut neighbors = Vec::new();
+        p.neighbors(&mut neighbors);
+        if include_self {
+            neighbors.push(p);
+        }
+
+        let (sum, count) = neighbors.into_iter()
+            .filter_map(|pt| self.try_get(pt))
+            .fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+        if count == 0 { 0.0 } else { sum / count as f64 }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::path::{L1, Linf};
+
+    fn testing_grid() -> Grid<f64> {
+        Grid::from_vec(
+            vec![
+                0.0, 4.0, 1.0,
+                2.0, 6.0, 3.0,
+            ], V2i(0, 0), V2i(3, 2),
+        ).expect("Creating the test grid failed")
+    }
+
+    #[test]
+    fn sample_bilinear() {
+        let grid = testing_grid();
+        assert_eq!(grid.sample_bilinear(V2f(0.5, 0.5)), Some(3.0));
+        assert_eq!(grid.sample_bilinear(V2f(1.0, 0.0)), Some(4.0));
+        assert_eq!(grid.sample_bilinear(V2f(2.0, 1.0)), Some(3.0));
+        assert_eq!(grid.sample_bilinear(V2f(1.5, 0.0)), Some(2.5));
+        assert_eq!(grid.sample_bilinear(V2f(0.25, 0.5)), Some(0.75 * 1.0 + 0.25 * 5.0));
+        assert_eq!(grid.sample_bilinear(V2f(2.5, 0.5)), None);
+        assert_eq!(grid.sample_bilinear(V2f(-0.5, 0.0)), None);
+    }
+
+    #[test]
+    fn splat() {
+        let mut grid: Grid<f64> = Grid::from_default(V2i(0, 0), V2i(3, 3)).unwrap();
+        grid.splat(V2f(1.0, 1.0), 2.0);
+        assert_eq!(*grid.get(V2i(1, 1)).unwrap(), 2.0);
+        assert_eq!(grid.iter().sum::<f64>(), 2.0);
+
+        grid.splat(V2f(0.5, 1.5), 4.0);
+        for &pt in &[V2i(0, 1), V2i(0, 2), V2i(1, 2)] {
+            assert_eq!(*grid.get(pt).unwrap(), 1.0);
+        }
+        assert_eq!(*grid.get(V2i(1, 1)).unwrap(), 3.0);
+
+        grid.splat(V2f(2.5, 0.0), 8.0);
+        assert_eq!(*grid.get(V2i(2, 0)).unwrap(), 4.0);
+        assert_eq!(grid.iter().sum::<f64>(), 10.0);
+    }
+
+    #[test]
+    fn neighbor_average() {
+        let grid = Grid::from_vec(
+            vec![
+                1.0, 2.0, 3.0,
+                4.0, 5.0, 6.0,
+                7.0, 8.0, 9.0,
+            ], V2i(0, 0), V2i(3, 3),
+        ).unwrap();
+        assert_eq!(grid.neighbor_average::<L1>(V2i(1, 1), false), 5.0);
+        assert_eq!(grid.neighbor_average::<Linf>(V2i(1, 1), true), 5.0);
+        assert_eq!(grid.neighbor_average::<L1>(V2i(0, 0), false), 3.0);
+        assert_eq!(grid.neighbor_average::<L1>(V2i(0, 0), true), 7.0 / 3.0);
+        assert_eq!(grid.neighbor_average::<Linf>(V2i(2, 1), false), (2.0 + 3.0 + 5.0 + 8.0 + 9.0) / 5.0);
+        assert_eq!(grid.neighbor_average::<L1>(V2i(-1, 0), true), 1.0);
+        assert_eq!(grid.neighbor_average::<L1>(V2i(5, 5), true), 0.0);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::*;
+use super::{Grid, path::Neighbors};
+#[cfg(feature = "rayon")]
+use super::{path::Traversable, region::Region};
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+
+/* Breadth-first search advanced one ring per step. The seed counts as already reached. */
+#[derive(Debug, Clone)]
+pub struct FrontierExpander {
+    visited: HashSet<V2i>,
+    frontier: Vec<V2i>,
+}
+
+impl FrontierExpander {
+    pub fn new(seed: V2i) -> FrontierExpander {
+        FrontierExpander {
+            visited: Some(seed).into_iter().collect(),
+            frontier: vec![seed],
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.frontier.is_empty()
+    }
+
+    pub fn step<N, A>(&mut self, mut allow: A) -> Vec<V2i>
+        where
+            V2i: Neighbors<N>,
+            A: FnMut(V2i) -> bool
+    {
+        let mut next = Vec::new();
+        let mut neighbors = Vec::new();
+        for pt in self.frontier.drain(..) {
+            pt.neighbors(&mut neighbors);
+            for neigh in neighbors.drain(..) {
+                if !self.visited.contains(&neigh) && allow(neigh) {
+                    self.visited.insert(neigh);
+                    next.push(neigh);
+                }
+            }
+        }
+        self.frontier = next.clone();
+        next
+    }
+}
+
+impl<T> Grid<T> {
+    /* Dijkstra from every source at once. Entering a cell costs cost(cell); None is impassable. */
+    pub fn spread<N, C>(&self, sources: &[V2i], budget: usize, cost: C) -> Grid<Option<usize>>
+        where
+            V2i: Neighbors<N>,
+            C: Fn(&T) -> Option<usize>
+    {
+        let mut dist: Grid<Option<usize>> = Grid::from_default(self.origin, self.dim).unwrap();
+        let mut open = BinaryHeap::new();
+        let mut neighbors = Vec::new();
+
+        for &src in sources {
+            if let Ok(tile) = self.get(src) {
+                if cost(tile).is_some() {
+                    *dist.get_mut(src).unwrap() = Some(0);
+                    open.push(Reverse((0usize, src)));
+                }
+            }
+        }
+
+        while let Some(Reverse((acc, node))) = open.pop() {
+            if dist.get(node).unwrap().is_some_and(|d| d < acc) {
+                continue;  // NB: Stale entry
+            }
+
+            node.neighbors(&mut neighbors);
+            for neigh in neighbors.drain(..) {
+                let step = match self.get(neigh).map(&cost) {
+                    Ok(Some(step)) => step,
+                    _ => continue,
+                };
+                let est = match acc.checked_add(step) {
+                    Some(est) if est <= budget => est,
+                    _ => continue,  // NB: Overflowing is over any budget
+                };
+                let slot = dist.get_mut(neigh).unwrap();
+                if slot.is_none_or(|d| est < d) {
+                    *slot = Some(est);
+                    open.push(Reverse((est, neigh)));
+                }
+            }
+        }
+
+        dist
+    }
+
+    /* Reachable cells in row-major order, with the movement left on arriving at each. */
+    pub fn movement_range<N, C>(&self, start: V2i, budget: usize, cost: C) -> Vec<(V2i, usize)>
+        where
+            V2i: Neighbors<N>,
+            C: Fn(&T) -> Option<usize>
+    {
+        let dist = self.spread::<N, C>(&[start], budget, cost);
+        dist.rect().iter().zip(dist.iter())
+            .filter_map(|(pt, d)| d.map(|d| (pt, budget - d)))
+            .collect()
+    }
+
+    /* Breadth-first fill from start over cells satisfying should_fill (tested before writing),
+     * stopping once max_cells are filled. Returns the filled cells in fill order and whether
+     * the budget cut the fill short. */
+    pub fn flood_fill_bounded<N, F>(&mut self, start: V2i, should_fill: F, new: T, max_cells: usize) -> (Vec<V2i>, bool)
+        where
+            V2i: Neighbors<N>,
+            F: Fn(&T) -> bool,
+            T: Clone
+    {
+        let fillable = |grid: &Grid<T>, pt: V2i| grid.get(pt).is_ok_and(&should_fill);
+        let mut filled = Vec::new();
+        let mut seen: Grid<bool> = Grid::from_default(self.origin, self.dim).unwrap();
+        let mut open = VecDeque::new();
+        let mut neighbors = Vec::new();
+
+        if fillable(self, start) {
+            open.push_back(start);
+            *seen.get_mut(start).unwrap() = true;
+        }
+        while let Some(pt) = open.pop_front() {
+            if filled.len() == max_cells {
+                return (filled, true);
+            }
+            *self.get_mut(pt).unwrap() = new.clone();
+            filled.push(pt);
+
+            pt.neighbors(&mut neighbors);
+            for neigh in neighbors.drain(..) {
+                if fillable(self, neigh) && !*seen.get(neigh).unwrap() {
+                    *seen.get_mut(neigh).unwrap() = true;
+                    open.push_back(neigh);
+                }
+            }
+        }
+        (filled, false)
+    }
+
+    /* Labels each cell with the index of its nearest seed under M, ties going to the lowest
+     * index. Cells are all usize::MAX if there are no seeds. */
+    pub fn voronoi<M: Metric>(&self, seeds: &[V2i]) -> Grid<usize> {
+        Grid::from_generator(|pt|
+            seeds.iter().enumerate()
+                .min_by_key(|&(i, &seed)| (M::dist(pt, seed), i))
+                .map_or(usize::MAX, |(i, _)| i),
+            self.origin, self.dim,
+        ).unwrap()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Traversable + Default> Region<T> {
+    /* Each source contributes strength * decay^steps to every cell it reaches within bounds,
+     * and contributions are summed. Unloaded cells are impassable, as are sources outside
+     * bounds. Passability is snapshotted up front so the per-source floods can run in parallel. */
+    pub fn influence_map<N>(&self, sources: &[(V2i, f64)], decay: f64, bounds: R2i) -> Grid<f64>
+        where
+            V2i: Neighbors<N>
+    {
+        use rayon::prelude::*;
+
+        let passable = Grid::from_generator(
+            |pt| self.get(pt).is_some_and(Traversable::can_pass),
+            bounds.origin(), bounds.dim(),
+        ).unwrap();
+        let zero = || Grid::from_default(bounds.origin(), bounds.dim()).unwrap();
+
+        sources.par_iter()
+            .map(|&(src, strength)| {
+                let dist = passable.spread::<N, _>(&[src], usize::MAX, |&p| if p { Some(1) } else { None });
+                let mut field: Grid<f64> = zero();
+                for (cell, d) in field.iter_mut().zip(dist.iter()) {
+                    if let Some(d) = d {
+                        *cell = strength * decay.powi(*d as i32);
+                    }
+                }
+                field
+            })
+            .reduce(zero, |mut a, b| {
+                a.iter_mut().zip(b.iter()).for_each(|(x, y)| *x += y);
+                a
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::path::{L1, Linf};
+
+    fn testing_grid() -> Grid<isize> {
+        Grid::from_vec(
+            vec![
+                1, 1, 1, 1, 1,
+                1, 5, 0, 1, 1,
+                1, 1, 0, 1, 1,
+                1, 1, 1, 1, 1,
+            ], V2i(0, 0), V2i(5, 4),
+        ).expect("Creating the test grid failed")
+    }
+
+    fn cost(t: &isize) -> Option<usize> {
+        if *t == 0 { None } else { Some(*t as usize) }
+    }
+
+    #[test]
+    fn spread_costs() {
+        let res = testing_grid().spread::<L1, _>(&[V2i(0, 0)], 3, cost);
+        println!("spread: {:?}", res);
+        assert_eq!(*res.get(V2i(0, 0)).unwrap(), Some(0));
+        assert_eq!(*res.get(V2i(2, 0)).unwrap(), Some(2));
+        assert_eq!(*res.get(V2i(3, 0)).unwrap(), Some(3));
+        assert_eq!(*res.get(V2i(0, 3)).unwrap(), Some(3));
+        assert_eq!(*res.get(V2i(1, 1)).unwrap(), None);
+        assert_eq!(*res.get(V2i(2, 1)).unwrap(), None);
+        assert_eq!(*res.get(V2i(4, 0)).unwrap(), None);
+        assert_eq!(*res.get(V2i(1, 2)).unwrap(), Some(3));
+    }
+
+    #[test]
+    fn spread_huge_costs() {
+        let huge = |t: &isize| if *t == 0 { None } else { Some(usize::MAX - 1) };
+        let res = testing_grid().spread::<L1, _>(&[V2i(0, 0)], usize::MAX, huge);
+        assert_eq!(*res.get(V2i(1, 0)).unwrap(), Some(usize::MAX - 1));
+        assert_eq!(*res.get(V2i(2, 0)).unwrap(), None);
+        assert_eq!(res.iter().filter(|d| d.is_some()).count(), 3);
+
+        let range = testing_grid().movement_range::<L1, _>(V2i(0, 0), usize::MAX, huge);
+        assert_eq!(range.len(), 3);
+    }
+
+    #[test]
+    fn spread_multi_source() {
+        let res = testing_grid().spread::<L1, _>(&[V2i(0, 0), V2i(4, 3), V2i(2, 2)], 1, cost);
+        println!("spread: {:?}", res);
+        assert_eq!(*res.get(V2i(4, 3)).unwrap(), Some(0));
+        assert_eq!(*res.get(V2i(3, 3)).unwrap(), Some(1));
+        assert_eq!(*res.get(V2i(1, 0)).unwrap(), Some(1));
+        assert_eq!(*res.get(V2i(2, 2)).unwrap(), None);
+        assert_eq!(res.iter().filter(|d| d.is_some()).count(), 6);
+    }
+
+    #[test]
+    fn movement_range() {
+        let range = testing_grid().movement_range::<L1, _>(V2i(1, 2), 2, cost);
+        println!("range: {:?}", range);
+        assert_eq!(range, vec![
+            (V2i(0, 1), 0),
+            (V2i(0, 2), 1), (V2i(1, 2), 2),
+            (V2i(0, 3), 0), (V2i(1, 3), 1), (V2i(2, 3), 0),
+        ]);
+        assert!(testing_grid().movement_range::<L1, _>(V2i(2, 2), 2, cost).is_empty());
+    }
+
+    #[test]
+    fn frontier_rings() {
+        let grid: Grid<()> = Grid::from_default(V2i(-3, -3), V2i(7, 7)).unwrap();
+        let mut fe = FrontierExpander::new(V2i(0, 0));
+        for dist in 1..=6 {
+            let ring = fe.step::<L1, _>(|pt| grid.contains(pt));
+            println!("ring {}: {:?}", dist, ring);
+            assert!(!ring.is_empty());
+            assert!(ring.iter().all(|pt| pt.l1() == dist));
+        }
+        assert!(fe.step::<L1, _>(|pt| grid.contains(pt)).is_empty());
+        assert!(fe.is_exhausted());
+
+        let mut fe = FrontierExpander::new(V2i(0, 0));
+        assert_eq!(fe.step::<Linf, _>(|pt| grid.contains(pt)).len(), 8);
+        assert_eq!(fe.step::<Linf, _>(|pt| grid.contains(pt)).len(), 16);
+    }
+
+    #[test]
+    fn voronoi_split() {
+        let grid: Grid<()> = Grid::from_default(V2i(0, 0), V2i(7, 3)).unwrap();
+        let vor = grid.voronoi::<L1>(&[V2i(1, 1), V2i(5, 1)]);
+        println!("voronoi: {:?}", vor);
+        for pt in grid.rect().iter() {
+            let expect = if pt.0 <= 3 { 0 } else { 1 };
+            assert_eq!(*vor.get(pt).unwrap(), expect);
+        }
+
+        let vor = grid.voronoi::<Linf>(&[V2i(0, 0), V2i(3, 2)]);
+        assert_eq!(*vor.get(V2i(1, 1)).unwrap(), 0);
+        assert_eq!(*vor.get(V2i(2, 1)).unwrap(), 1);
+        assert_eq!(*vor.get(V2i(1, 2)).unwrap(), 0);
+        assert!(grid.voronoi::<L1>(&[]).iter().all(|&i| i == usize::MAX));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn influence_map_matches_sequential() {
+        use crate::grid::region::RegionConfig;
+
+        let mut region = RegionConfig::<isize>::default().with_grid_size(V2i(4, 4))
+            .build().expect("Failed to build Region");
+        let bounds = R2i::origin_dim(V2i(-4, -4), V2i(8, 8));
+        for pt in R2i::origin_dim(V2i(0, -4), V2i(4, 4)).iter() {
+            region.get_mut(pt);
+        }
+        for y in -4..4 {
+            *region.get_mut(V2i(-1, y)) = 1;
+        }
+
+        let sources = [(V2i(-3, -3), 2.0), (V2i(2, -2), 1.0), (V2i(-3, 2), 5.0), (V2i(2, 2), 9.0)];
+        let map = region.influence_map::<L1>(&sources, 0.5, bounds);
+
+        let tiles = Grid::from_generator(|pt| region.get_copied(pt, 1), bounds.origin(), bounds.dim()).unwrap();
+        for pt in bounds.iter() {
+            let expected: f64 = sources.iter().map(|&(src, strength)| {
+                let dist = tiles.spread::<L1, _>(&[src], usize::MAX, |&t| if t == 0 { Some(1) } else { None });
+                dist.get(pt).unwrap().map_or(0.0, |d| strength * 0.5f64.powi(d as i32))
+            }).sum();
+            assert!((map.get(pt).unwrap() - expected).abs() < 1e-12, "at {:?}", pt);
+        }
+        assert_eq!(*map.get(V2i(-3, -3)).unwrap(), 2.0 + 5.0 * 0.5f64.powi(5));
+        assert_eq!(*map.get(V2i(-1, 0)).unwrap(), 0.0);
+        assert_eq!(*map.get(V2i(0, -4)).unwrap(), 0.5f64.powi(4));
+        assert_eq!(*map.get(V2i(2, 2)).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn flood_fill_bounded() {
+        let mut grid: Grid<isize> = Grid::from_default(V2i(0, 0), V2i(20, 20)).unwrap();
+        for y in 0..20 {
+            *grid.get_mut(V2i(10, y)).unwrap() = 1;
+        }
+
+        let (filled, truncated) = grid.flood_fill_bounded::<L1, _>(V2i(2, 2), |&t| t == 0, 5, 30);
+        assert!(truncated);
+        assert_eq!(filled.len(), 30);
+        assert_eq!(filled[0], V2i(2, 2));
+        assert_eq!(grid.iter().filter(|&&t| t == 5).count(), 30);
+        assert!(filled.iter().all(|&pt| *grid.get(pt).unwrap() == 5 && L1::dist(pt, V2i(2, 2)) <= 4));
+
+        let (filled, truncated) = grid.flood_fill_bounded::<L1, _>(V2i(15, 0), |&t| t == 0, 6, 1000);
+        assert!(!truncated);
+        assert_eq!(filled.len(), 9 * 20);
+        assert_eq!(*grid.get(V2i(9, 0)).unwrap(), 0);
+
+        let (filled, truncated) = grid.flood_fill_bounded::<L1, _>(V2i(15, 0), |&t| t == 6, 7, 180);
+        assert_eq!((filled.len(), truncated), (180, false));
+        assert_eq!(grid.flood_fill_bounded::<L1, _>(V2i(10, 3), |&t| t == 0, 8, 10), (vec![], false));
+        assert_eq!(grid.flood_fill_bounded::<L1, _>(V2i(-1, 3), |&t| t == 0, 8, 10), (vec![], false));
+        assert_eq!(grid.flood_fill_bounded::<L1, _>(V2i(2, 2), |&t| t == 5, 8, 0), (vec![], true));
+    }
+}
@@ -1,23 +1,128 @@
+pub mod component;
+#[cfg(feature = "rand")]
+pub mod dungeon;
+pub mod edit;
+pub mod field;
+pub mod flood;
+pub mod nibble;
 pub mod path;
 pub mod region;
+pub mod trace;
 
 use crate::*;
 
 use std::fmt::{self, Debug};
 use std::iter;
+use std::slice;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawGrid<T>"))]
 pub struct Grid<T> {
     array: Box<[T]>,
     origin: V2i,
     dim: V2i,
 }
 
+/* Walks the Hilbert curve over the smallest power-of-two square covering the grid, skipping
+ * points outside of it; consecutive cells are only guaranteed adjacent for power-of-two squares. */
+#[derive(Debug, Clone)]
+pub struct HilbertIter<'a, T> {
+    grid: &'a Grid<T>,
+    side: usize,
+    index: usize,
+}
+
+impl<'a, T> Iterator for HilbertIter<'a, T> {
+    type Item = (V2i, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.side * self.side {
+            let (mut t, mut pt, mut s) = (self.index, V2i::ZERO, 1);
+            self.index += 1;
+            while s < self.side {
+                let rx = 1 & (t / 2) as Vi;
+                let ry = 1 & (t as Vi ^ rx);
+                if ry == 0 {
+                    if rx == 1 {
+                        pt = V2i(s as Vi - 1, s as Vi - 1) - pt;
+                    }
+                    pt = pt.swap();
+                }
+                pt += V2i(rx, ry) * V2i(s as Vi, s as Vi);
+                t /= 4;
+                s *= 2;
+            }
+
+            let pt = pt + self.grid.origin;
+            if let Ok(tile) = self.grid.get(pt) {
+                return Some((pt, tile));
+            }
+        }
+        None
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     NegativeDim(V2i),
     BadDim(V2i, usize),
-    OutOfBounds(V2i),
+    /* axis is None when the point is out of range on both axes */
+    OutOfBounds { point: V2i, axis: Option<Axis>, rect: R2i },
     BadIndex(usize),
+    MismatchedRect(R2i, R2i),
+    /* (dim, tile_dim) */
+    IndivisibleDim(V2i, V2i),
+    /* A value too wide for a packed cell */
+    ValueOutOfRange(u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/* Deserialized fields, validated through from_boxed_slice before becoming a Grid */
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawGrid<T> {
+    array: Box<[T]>,
+    origin: V2i,
+    dim: V2i,
+}
+
+#[cfg(feature = "serde")]
+impl<T> std::convert::TryFrom<RawGrid<T>> for Grid<T> {
+    type Error = Error;
+
+    fn try_from(raw: RawGrid<T>) -> Result<Grid<T>, Error> {
+        Grid::from_boxed_slice(raw.array, raw.origin, raw.dim)
+    }
+}
+
+#[derive(Debug)]
+pub enum GenError<E> {
+    Grid(Error),
+    Generator(V2i, E),
+}
+
+/* Shared by every row-major grid layout */
+fn row_major_index(rect: R2i, v: V2i) -> Result<usize, Error> {
+    let d = v - rect.origin();
+    let dim = rect.dim();
+    let (bad_x, bad_y) = (d.0 < 0 || d.0 >= dim.0, d.1 < 0 || d.1 >= dim.1);
+    if bad_x || bad_y {
+        return Err(Error::OutOfBounds {
+            point: v,
+            axis: match (bad_x, bad_y) {
+                (true, false) => Some(Axis::X),
+                (false, true) => Some(Axis::Y),
+                _ => None,
+            },
+            rect,
+        });
+    }
+    Ok(d.1 as usize * dim.0 as usize + d.0 as usize)
 }
 
 impl<T> Grid<T> {
@@ -39,7 +144,7 @@ impl<T> Grid<T> {
         })
     }
 
-    pub fn from_generator<G>(mut gen: G, origin: V2i, dim: V2i) -> Result<Grid<T>, Error>
+    pub fn from_generator<G>(gen: G, origin: V2i, dim: V2i) -> Result<Grid<T>, Error>
         where
             G: FnMut(V2i) -> T
     {
@@ -48,17 +153,27 @@ impl<T> Grid<T> {
         }
 
         Grid::from_boxed_slice(
-            R2i::origin_dim(origin, dim).iter().map(move |pt| gen(pt)).collect(),
+            R2i::origin_dim(origin, dim).iter().map(gen).collect(),
             origin, dim,
         )
     }
 
-    pub fn index_of(&self, v: V2i) -> Result<usize, Error> {
-        let d = v - self.origin;
-        if d.0 < 0 || d.1 < 0 || d.0 >= self.dim.0 || d.1 >= self.dim.1 {
-            return Err(Error::OutOfBounds(v));
+    pub fn try_from_generator<G, E>(mut gen: G, origin: V2i, dim: V2i) -> Result<Grid<T>, GenError<E>>
+        where
+            G: FnMut(V2i) -> Result<T, E>
+    {
+        if !dim.is_q1() {
+            return Err(GenError::Grid(Error::NegativeDim(dim)));
         }
-        Ok(d.1 as usize * self.dim.0 as usize + d.0 as usize)
+
+        let array = R2i::origin_dim(origin, dim).iter()
+            .map(|pt| gen(pt).map_err(|e| GenError::Generator(pt, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Grid::from_vec(array, origin, dim).map_err(GenError::Grid)
+    }
+
+    pub fn index_of(&self, v: V2i) -> Result<usize, Error> {
+        row_major_index(self.rect(), v)
     }
 
     pub fn v2i_of(&self, index: usize) -> Result<V2i, Error> {
@@ -78,6 +193,27 @@ impl<T> Grid<T> {
         self.index_of(v).map(move |i| &mut self.array[i])
     }
 
+    pub fn set(&mut self, v: V2i, value: T) -> Result<(), Error> {
+        *self.get_mut(v)? = value;
+        Ok(())
+    }
+
+    pub fn try_get(&self, v: V2i) -> Option<&T> {
+        self.get(v).ok()
+    }
+
+    pub fn try_get_mut(&mut self, v: V2i) -> Option<&mut T> {
+        self.get_mut(v).ok()
+    }
+
+    /* Moore neighborhood clockwise from north (-y): N, NE, E, SE, S, SW, W, NW. */
+    pub fn neighbors8(&self, v: V2i) -> [Option<&T>; 8] {
+        [
+            V2i(0, -1), V2i(1, -1), V2i(1, 0), V2i(1, 1),
+            V2i(0, 1), V2i(-1, 1), V2i(-1, 0), V2i(-1, -1),
+        ].map(|d| self.try_get(v + d))
+    }
+
     pub fn array(&self) -> &[T] {
         self.array.as_ref()
     }
@@ -86,6 +222,130 @@ impl<T> Grid<T> {
         self.array.as_mut()
     }
 
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.array.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.array.iter_mut()
+    }
+
+    pub fn iter_hilbert(&self) -> HilbertIter<'_, T> {
+        HilbertIter {
+            grid: self,
+            side: (self.dim.cmax().max(1) as usize).next_power_of_two(),
+            index: 0,
+        }
+    }
+
+    /* Counts horizontally and vertically adjacent pairs, each passed as (lower, higher). */
+    pub fn transitions<F>(&self, is_transition: F) -> usize
+        where
+            F: Fn(&T, &T) -> bool
+    {
+        let w = self.dim.0 as usize;
+        let horiz = self.array.chunks(w.max(1))
+            .flat_map(|row| row.windows(2))
+            .filter(|p| is_transition(&p[0], &p[1]))
+            .count();
+        let vert = self.array.iter().zip(self.array.iter().skip(w))
+            .filter(|(a, b)| is_transition(a, b))
+            .count();
+        horiz + vert
+    }
+
+    pub fn centroid<F>(&self, pred: F) -> Option<V2f>
+        where
+            F: Fn(&T) -> bool
+    {
+        let (sum, count) = self.rect().iter().zip(self.array.iter())
+            .filter(|(_, t)| pred(t))
+            .fold((V2f::ZERO, 0usize), |(sum, count), (pt, _)| (sum + V2f::from(pt), count + 1));
+        if count == 0 {
+            None
+        } else {
+            Some(sum / V2f(count as Vf, count as Vf))
+        }
+    }
+
+    /* Cells of rect (clipped to the grid) satisfying pred, in row-major order. */
+    pub fn query<'a, F>(&'a self, rect: R2i, pred: F) -> impl Iterator<Item=(V2i, &'a T)> + 'a
+        where
+            F: Fn(&T) -> bool + 'a
+    {
+        let clipped = self.rect().intersect(rect).unwrap_or(R2i::origin_dim(self.origin, V2i::ZERO));
+        clipped.iter()
+            .map(move |pt| (pt, &self.array[self.index_of(pt).unwrap()]))
+            .filter(move |(_, t)| pred(t))
+    }
+
+    /* Cells on the outermost ring of the grid satisfying open, each counted once. */
+    pub fn border_openings<F>(&self, open: F) -> usize
+        where
+            F: Fn(&T) -> bool
+    {
+        self.rect().border_iter().filter(|&pt| open(self.get(pt).unwrap())).count()
+    }
+
+    /* Every all-empty rect that can't grow in any direction, found bottom row by bottom row with
+     * the largest-rectangle-in-histogram stack. */
+    pub fn maximal_empty_rects<F>(&self, empty: F) -> Vec<R2i>
+        where
+            F: Fn(&T) -> bool
+    {
+        let (w, h) = (self.dim.0 as usize, self.dim.1 as usize);
+        let is_empty = |x: usize, y: usize| empty(&self.array[y * w + x]);
+        let mut heights = vec![0usize; w];
+        let mut stack: Vec<(usize, usize)> = Vec::new();  // NB: (left column, bar height), heights increasing
+        let mut rects = Vec::new();
+
+        for y in 0..h {
+            for (x, height) in heights.iter_mut().enumerate() {
+                *height = if is_empty(x, y) { *height + 1 } else { 0 };
+            }
+
+            for (x, cur) in heights.iter().copied().chain(Some(0)).enumerate() {  // NB: Sentinel flushes the stack
+                let mut left = x;
+                while let Some(&(s, bar)) = stack.last() {
+                    if bar < cur {
+                        break;
+                    }
+                    stack.pop();
+                    left = s;
+                    /* Bounded left, right and above by construction; only a fully empty row
+                     * beneath the span could still extend it. */
+                    if bar > cur && !(y + 1 < h && (s..x).all(|sx| is_empty(sx, y + 1))) {
+                        rects.push(R2i::origin_dim(
+                            self.origin + V2i(s as Vi, (y + 1 - bar) as Vi),
+                            V2i((x - s) as Vi, bar as Vi),
+                        ));
+                    }
+                }
+                if cur > 0 {
+                    stack.push((left, cur));
+                }
+            }
+        }
+        rects
+    }
+
+    /* The first of the largest maximal_empty_rects by area, if any cell is empty. */
+    pub fn largest_empty_rect<F>(&self, empty: F) -> Option<R2i>
+        where
+            F: Fn(&T) -> bool
+    {
+        self.maximal_empty_rects(empty).into_iter()
+            .fold(None, |best: Option<R2i>, r| match best {
+                Some(b) if b.area() >= r.area() => Some(b),
+                _ => Some(r),
+            })
+    }
+
+    /* NB: from_vec shrinks the Vec to fit, so the returned Vec has no spare capacity. */
+    pub fn into_vec(self) -> Vec<T> {
+        self.array.into_vec()
+    }
+
     pub fn rect(&self) -> R2i {
         R2i::origin_dim(self.origin, self.dim)
     }
@@ -99,6 +359,126 @@ impl<T: Clone> Clone for Grid<T> {
     }
 }
 
+impl<T: PartialEq> Grid<T> {
+    /* Yields the cells of other which differ from self, as (coordinate, new value). */
+    pub fn diff<'a>(&self, other: &'a Grid<T>) -> Result<Vec<(V2i, &'a T)>, Error> {
+        if self.rect() != other.rect() {
+            return Err(Error::MismatchedRect(self.rect(), other.rect()));
+        }
+
+        Ok(self.rect().iter()
+            .zip(self.array.iter().zip(other.array.iter()))
+            .filter(|(_, (old, new))| old != new)
+            .map(|(pt, (_, new))| (pt, new))
+            .collect())
+    }
+
+    /* Row-major, skipping cells equal to T::default(). */
+    pub fn iter_non_default(&self) -> impl Iterator<Item=(V2i, &T)> + '_
+        where
+            T: Default
+    {
+        let default = T::default();
+        self.rect().iter().zip(self.iter()).filter(move |(_, t)| **t != default)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /* rect must lie within the grid */
+    fn sub_grid(&self, rect: R2i) -> Grid<T> {
+        Grid::from_generator(|pt| self.get(pt).unwrap().clone(), rect.origin(), rect.dim()).unwrap()
+    }
+
+    /* Every placement of a size window fully inside the grid, in row-major order, keyed by the
+     * window's origin. */
+    pub fn windows(&self, size: V2i) -> impl Iterator<Item=(V2i, Grid<T>)> + '_ {
+        let span = self.dim - size + V2i::ONE;
+        let positions = if size.is_strict_q1() && span.is_strict_q1() {
+            R2i::origin_dim(self.origin, span)
+        } else {
+            R2i::origin_dim(self.origin, V2i::ZERO)
+        };
+        positions.iter().map(move |pos| (pos, self.sub_grid(R2i::origin_dim(pos, size))))
+    }
+
+    /* Frames interior in border cells of wall on every side; interior keeps its coordinates, so
+     * the result's origin is interior's shifted by -border. */
+    pub fn with_walled_border(interior: Grid<T>, border: Vi, wall: T) -> Grid<T> {
+        let border = border.max(0);
+        let pad = V2i(border, border);
+        Grid::from_generator(
+            |pt| interior.try_get(pt).unwrap_or(&wall).clone(),
+            interior.origin - pad, interior.dim + pad * 2,
+        ).unwrap()
+    }
+
+    /* Row-major, keyed by tile index counted from the grid origin; each tile keeps its world
+     * coordinates. */
+    pub fn tiles(&self, tile_dim: V2i) -> Result<Vec<(V2i, Grid<T>)>, Error> {
+        if !tile_dim.is_strict_q1() || self.dim.rem_euclid(tile_dim) != V2i::ZERO {
+            return Err(Error::IndivisibleDim(self.dim, tile_dim));
+        }
+        Ok(R2i::origin_dim(V2i::ZERO, self.dim / tile_dim).iter()
+            .map(|idx| (idx, self.sub_grid(R2i::origin_dim(self.origin + idx * tile_dim, tile_dim))))
+            .collect())
+    }
+
+    /* Checks every coordinate before writing anything, so a bad change set leaves self intact. */
+    pub fn apply_diff(&mut self, changes: &[(V2i, T)]) -> Result<(), Error> {
+        let indices = changes.iter()
+            .map(|(pt, _)| self.index_of(*pt))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, (_, value)) in indices.into_iter().zip(changes) {
+            self.array[i] = value.clone();
+        }
+        Ok(())
+    }
+
+    /* Clockwise as displayed with +y downward; the origin stays put. */
+    pub fn rotate_90(&self) -> Grid<T> {
+        self.rotate(1)
+    }
+
+    pub fn rotate(&self, quarter_turns: u32) -> Grid<T> {
+        let (w, h) = (self.dim.0, self.dim.1);
+        let turns = quarter_turns % 4;
+        let dim = if turns % 2 == 1 { self.dim.swap() } else { self.dim };
+        Grid::from_generator(|pt| {
+            let d = pt - self.origin;
+            let src = match turns {
+                0 => d,
+                1 => V2i(d.1, h - 1 - d.0),
+                2 => V2i(w - 1 - d.0, h - 1 - d.1),
+                _ => V2i(w - 1 - d.1, d.0),
+            };
+            self.get(self.origin + src).unwrap().clone()
+        }, self.origin, dim).unwrap()
+    }
+
+    /* Reflects across the anti-diagonal; cell (x, y) lands at (dim.1-1-y, dim.0-1-x) relative
+     * to the (unchanged) origin. */
+    pub fn flip_anti_diagonal(&self) -> Grid<T> {
+        let dim = self.dim.swap();
+        Grid::from_generator(|pt| {
+            let d = pt - self.origin;
+            self.get(self.origin + V2i(self.dim.0 - 1 - d.1, self.dim.1 - 1 - d.0)).unwrap().clone()
+        }, self.origin, dim).unwrap()
+    }
+}
+
+#[cfg(feature = "image")]
+impl<T> Grid<T> {
+    /* One pixel per cell, row-major, with the grid origin at the top-left pixel. */
+    pub fn to_image<F>(&self, color: F) -> image::RgbImage
+        where
+            F: Fn(&T) -> [u8; 3]
+    {
+        image::RgbImage::from_fn(self.dim.0 as u32, self.dim.1 as u32, |x, y|
+            image::Rgb(color(&self.array[y as usize * self.dim.0 as usize + x as usize]))
+        )
+    }
+}
+
 impl<T: Debug> Debug for Grid<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Grid")
@@ -122,6 +502,24 @@ impl<T: Default> Grid<T> {
     }
 }
 
+impl<T: Default + Clone> Grid<T> {
+    /* Reallocates to rect, keeping cells that lie in both rects and default-filling the rest. */
+    pub fn resize(&mut self, rect: R2i) {
+        let grid = Grid::from_generator(
+            |pt| self.try_get(pt).cloned().unwrap_or_default(),
+            rect.origin(), rect.dim(),
+        ).unwrap();
+        *self = grid;
+    }
+
+    /* Grows to the bounding box of the current rect and p; does nothing if p is already inside. */
+    pub fn grow_to_include(&mut self, p: V2i) {
+        if !self.contains(p) {
+            self.resize(self.rect().union(R2i::origin_dim(p, V2i::ONE)));
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -170,4 +568,382 @@ mod test {
             assert_eq!(i as usize, grid.index_of(pt).expect("Failed to get index"));
         }
     }
+
+    #[test]
+    fn try_from_generator() {
+        let grid = Grid::try_from_generator(|pt| Ok::<_, ()>(pt.l1()), V2i(-1, -1), V2i(3, 3)).expect("Failed to generate");
+        assert_eq!(*grid.get(V2i(1, -1)).unwrap(), 2);
+
+        let res: Result<Grid<isize>, _> = Grid::try_from_generator(|pt| {
+            if pt == V2i(2, 1) { Err("bad tile") } else { Ok(0) }
+        }, V2i(0, 0), V2i(SIZE, SIZE));
+        match res {
+            Err(GenError::Generator(pt, e)) => {
+                assert_eq!(pt, V2i(2, 1));
+                assert_eq!(e, "bad tile");
+            },
+            res => panic!("Unexpected result {:?}", res),
+        }
+
+        let res: Result<Grid<isize>, GenError<()>> = Grid::try_from_generator(|_| Ok(0), V2i(0, 0), V2i(-1, 1));
+        assert!(matches!(res, Err(GenError::Grid(Error::NegativeDim(_)))));
+    }
+
+    #[test]
+    fn out_of_bounds_axis() {
+        let grid: Grid<isize> = Grid::from_default(V2i(-3, -3), V2i(SIZE, SIZE)).unwrap();
+        for &(pt, axis) in &[
+            (V2i(2, 0), Some(Axis::X)),
+            (V2i(-4, 1), Some(Axis::X)),
+            (V2i(0, 2), Some(Axis::Y)),
+            (V2i(-1, -4), Some(Axis::Y)),
+            (V2i(2, 2), None),
+        ] {
+            match grid.index_of(pt) {
+                Err(Error::OutOfBounds { point, axis: a, rect }) => {
+                    assert_eq!(point, pt);
+                    assert_eq!(a, axis);
+                    assert_eq!(rect, grid.rect());
+                },
+                res => panic!("Unexpected result {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn transitions() {
+        let grid = Grid::from_vec(
+            vec![
+                1, 1, 1, 1, 1,
+                1, 0, 0, 0, 1,
+                1, 0, 1, 0, 1,
+                1, 0, 1, 0, 1,
+                1, 1, 1, 1, 1,
+            ], V2i(0, 0), V2i(5, 5),
+        ).unwrap();
+        assert_eq!(grid.transitions(|a, b| a != b), 16);
+        assert_eq!(grid.transitions(|a, b| *a == 0 && *b == 1), 8);
+        assert_eq!(testing_grid().transitions(|a, b| a != b), 0);
+    }
+
+    #[test]
+    fn centroid() {
+        let mut grid = testing_grid();
+        for pt in R2i::origin_dim(V2i(1, 2), V2i(2, 3)).iter() {
+            grid.set(pt, 1).unwrap();
+        }
+        assert_eq!(grid.centroid(|&t| t == 1), Some(V2f(1.5, 3.0)));
+        assert_eq!(grid.centroid(|&t| t == 2), None);
+    }
+
+    #[test]
+    fn into_vec() {
+        let grid = Grid::from_vec((0..SIZE * SIZE).collect(), V2i(1, 1), V2i(SIZE, SIZE)).unwrap();
+        let v = grid.into_vec();
+        assert_eq!(v.len(), (SIZE * SIZE) as usize);
+        assert_eq!(v, (0..SIZE * SIZE).collect::<Vec<_>>());
+        let regrid = Grid::from_vec(v, V2i(0, 0), V2i(SIZE * SIZE, 1)).expect("Failed to reuse buffer");
+        assert_eq!(*regrid.get(V2i(7, 0)).unwrap(), 7);
+    }
+
+    #[test]
+    fn set() {
+        let mut grid = testing_grid();
+        grid.set(V2i(2, 3), 4).expect("Failed to set cell");
+        assert_eq!(*grid.get(V2i(2, 3)).unwrap(), 4);
+        match grid.set(V2i(2, SIZE), 4) {
+            Err(Error::OutOfBounds { point, .. }) => assert_eq!(point, V2i(2, SIZE)),
+            res => panic!("Unexpected result {:?}", res),
+        }
+        assert_eq!(grid.iter().sum::<isize>(), 4);
+    }
+
+    #[test]
+    fn try_get() {
+        let mut grid = testing_grid();
+        *grid.try_get_mut(V2i(1, 2)).expect("Failed to get cell") = 3;
+        assert_eq!(grid.try_get(V2i(1, 2)), Some(&3));
+        assert_eq!(grid.try_get(V2i(0, 0)), Some(&0));
+        assert_eq!(grid.try_get(V2i(SIZE, 0)), None);
+        assert_eq!(grid.try_get(V2i(0, -1)), None);
+        assert!(grid.try_get_mut(V2i(-1, SIZE)).is_none());
+    }
+
+    #[test]
+    fn neighbors8() {
+        let grid: Grid<isize> = Grid::from_generator(|pt| pt.1 * SIZE + pt.0, V2i(0, 0), V2i(SIZE, SIZE)).unwrap();
+        assert_eq!(grid.neighbors8(V2i(0, 0)), [None, None, Some(&1), Some(&6), Some(&5), None, None, None]);
+        assert_eq!(grid.neighbors8(V2i(SIZE - 1, SIZE - 1)), [Some(&19), None, None, None, None, None, Some(&23), Some(&18)]);
+        assert_eq!(grid.neighbors8(V2i(1, 1)), [Some(&1), Some(&2), Some(&7), Some(&12), Some(&11), Some(&10), Some(&5), Some(&0)]);
+    }
+
+    #[test]
+    fn diff() {
+        let grid = testing_grid();
+        let mut changed = grid.clone();
+        *changed.get_mut(V2i(1, 1)).unwrap() = 3;
+        *changed.get_mut(V2i(4, 0)).unwrap() = 5;
+        *changed.get_mut(V2i(2, 2)).unwrap() = 0;
+        assert_eq!(grid.diff(&changed).expect("Failed to diff"), vec![(V2i(4, 0), &5), (V2i(1, 1), &3)]);
+        assert!(grid.diff(&grid).expect("Failed to diff").is_empty());
+
+        let moved: Grid<isize> = Grid::from_default(V2i(1, 0), V2i(SIZE, SIZE)).unwrap();
+        assert!(grid.diff(&moved).is_err());
+    }
+
+    #[test]
+    fn apply_diff() {
+        let mut grid = testing_grid();
+        let mut changed = grid.clone();
+        *changed.get_mut(V2i(0, 3)).unwrap() = 7;
+        *changed.get_mut(V2i(2, 4)).unwrap() = -1;
+        let changes: Vec<_> = grid.diff(&changed).unwrap().into_iter().map(|(pt, v)| (pt, *v)).collect();
+
+        assert!(grid.apply_diff(&[(V2i(1, 1), 9), (V2i(SIZE, 0), 9)]).is_err());
+        assert_eq!(grid.array(), testing_grid().array());
+
+        grid.apply_diff(&changes).expect("Failed to apply diff");
+        assert_eq!(grid.array(), changed.array());
+    }
+
+    #[test]
+    fn iter_hilbert() {
+        let grid: Grid<isize> = Grid::from_generator(|pt| pt.0 * 8 + pt.1, V2i(-2, 3), V2i(8, 8)).unwrap();
+        let cells: Vec<_> = grid.iter_hilbert().collect();
+        assert_eq!(cells.len(), grid.array().len());
+        for pair in cells.windows(2) {
+            assert_eq!((pair[0].0 - pair[1].0).l1(), 1);
+        }
+        for &(pt, v) in &cells {
+            assert_eq!(grid.get(pt).unwrap(), v);
+        }
+        let mut pts: Vec<_> = cells.iter().map(|&(pt, _)| pt).collect();
+        pts.sort_by_key(|pt| pt.swap());
+        assert_eq!(pts, grid.rect().iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn iter_hilbert_odd() {
+        let grid = testing_grid();
+        let mut pts: Vec<_> = grid.iter_hilbert().map(|(pt, _)| pt).collect();
+        pts.sort_by_key(|pt| pt.swap());
+        assert_eq!(pts, grid.rect().iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn windows() {
+        let grid = Grid::from_vec((0..9).collect(), V2i(0, 0), V2i(3, 3)).unwrap();
+        let wins: Vec<_> = grid.windows(V2i(2, 2)).collect();
+        assert_eq!(wins.len(), 4);
+        assert_eq!(wins[0].0, V2i(0, 0));
+        assert_eq!(wins[0].1.array(), &[0, 1, 3, 4]);
+        assert_eq!(wins[3].0, V2i(1, 1));
+        assert_eq!(wins[3].1.array(), &[4, 5, 7, 8]);
+        assert_eq!(wins[3].1.rect(), R2i::origin_dim(V2i(1, 1), V2i(2, 2)));
+        assert_eq!(grid.windows(V2i(3, 1)).count(), 3);
+        assert_eq!(grid.windows(V2i(4, 1)).count(), 0);
+        assert_eq!(grid.windows(V2i(0, 1)).count(), 0);
+    }
+
+    #[test]
+    fn rotate() {
+        let grid = Grid::from_vec((0..6).collect(), V2i(2, -1), V2i(3, 2)).unwrap();
+        let cw = grid.rotate_90();
+        assert_eq!(cw.rect(), R2i::origin_dim(V2i(2, -1), V2i(2, 3)));
+        assert_eq!(cw.array(), &[3, 0, 4, 1, 5, 2]);
+
+        let mut repeated = grid.clone();
+        for turns in 0..8 {
+            let rotated = grid.rotate(turns);
+            assert_eq!(rotated.rect(), repeated.rect());
+            assert_eq!(rotated.array(), repeated.array());
+            repeated = repeated.rotate_90();
+        }
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn to_image() {
+        let mut grid: Grid<isize> = Grid::from_default(V2i(-2, 4), V2i(3, 2)).unwrap();
+        grid.set(V2i(-1, 5), 1).unwrap();
+        let img = grid.to_image(|&t| if t == 0 { [0, 0, 0] } else { [255, 128, 0] });
+        assert_eq!(img.dimensions(), (3, 2));
+        assert_eq!(img.get_pixel(1, 1).0, [255, 128, 0]);
+        assert_eq!(img.get_pixel(0, 0).0, [0, 0, 0]);
+        assert_eq!(img.get_pixel(1, 0).0, [0, 0, 0]);
+    }
+
+    #[test]
+    fn flip_anti_diagonal() {
+        let grid = Grid::from_vec((0..6).collect(), V2i(-1, 2), V2i(3, 2)).expect("Creating grid failed");
+        let flipped = grid.flip_anti_diagonal();
+        println!("flipped: {:?}", flipped);
+        assert_eq!(flipped.rect(), R2i::origin_dim(V2i(-1, 2), V2i(2, 3)));
+        assert_eq!(flipped.array(), &[5, 2, 4, 1, 3, 0]);
+        let back = flipped.flip_anti_diagonal();
+        assert_eq!(back.rect(), grid.rect());
+        assert_eq!(back.array(), grid.array());
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut grid = testing_grid();
+        for (i, v) in grid.iter_mut().enumerate() {
+            *v = i as isize;
+        }
+        grid.iter_mut().for_each(|v| *v *= 2);
+        for (i, v) in grid.iter().enumerate() {
+            assert_eq!(*v, 2 * i as isize);
+        }
+    }
+
+    #[test]
+    fn grow_to_include() {
+        let mut grid = testing_grid();
+        grid.set(V2i(1, 2), 7).unwrap();
+        assert!(grid.set(V2i(-2, SIZE), 3).is_err());
+
+        grid.grow_to_include(V2i(-2, SIZE));
+        assert_eq!(grid.rect(), R2i::origin_dim(V2i(-2, 0), V2i(SIZE + 2, SIZE + 1)));
+        assert_eq!(*grid.get(V2i(1, 2)).unwrap(), 7);
+        assert_eq!(*grid.get(V2i(SIZE - 1, 0)).unwrap(), 0);
+        grid.set(V2i(-2, SIZE), 3).unwrap();
+        assert_eq!(*grid.get(V2i(-2, SIZE)).unwrap(), 3);
+
+        let before = grid.clone().into_vec();
+        grid.grow_to_include(V2i(0, 0));
+        assert_eq!(grid.into_vec(), before);
+    }
+
+    #[test]
+    fn tiles() {
+        let grid = Grid::from_generator(|pt| pt.0 + 10 * pt.1, V2i(-1, 2), V2i(4, 4)).unwrap();
+        let tiles = grid.tiles(V2i(2, 2)).unwrap();
+        let indices: Vec<V2i> = tiles.iter().map(|(idx, _)| *idx).collect();
+        assert_eq!(indices, vec![V2i(0, 0), V2i(1, 0), V2i(0, 1), V2i(1, 1)]);
+        assert_eq!(tiles[0].1.rect(), R2i::origin_dim(V2i(-1, 2), V2i(2, 2)));
+        assert_eq!(tiles[0].1.clone().into_vec(), vec![19, 20, 29, 30]);
+        assert_eq!(tiles[1].1.clone().into_vec(), vec![21, 22, 31, 32]);
+        assert_eq!(tiles[2].1.clone().into_vec(), vec![39, 40, 49, 50]);
+        assert_eq!(tiles[3].1.clone().into_vec(), vec![41, 42, 51, 52]);
+
+        assert_eq!(grid.tiles(V2i(4, 1)).unwrap().len(), 4);
+        assert!(matches!(grid.tiles(V2i(3, 2)), Err(Error::IndivisibleDim(_, _))));
+        assert!(matches!(grid.tiles(V2i(0, 2)), Err(Error::IndivisibleDim(_, _))));
+    }
+
+    #[test]
+    fn with_walled_border() {
+        let interior = Grid::from_generator(|pt| pt.0 + 10 * pt.1, V2i(1, 1), V2i(3, 3)).unwrap();
+        let grid = Grid::with_walled_border(interior, 1, -1);
+        assert_eq!(grid.rect(), R2i::origin_dim(V2i(0, 0), V2i(5, 5)));
+        for pt in grid.rect().iter() {
+            let on_edge = pt.0 == 0 || pt.1 == 0 || pt.0 == 4 || pt.1 == 4;
+            let expected = if on_edge { -1 } else { pt.0 + 10 * pt.1 };
+            assert_eq!(*grid.get(pt).unwrap(), expected, "at {:?}", pt);
+        }
+
+        let thick = Grid::with_walled_border(grid, 2, -2);
+        assert_eq!(thick.rect(), R2i::origin_dim(V2i(-2, -2), V2i(9, 9)));
+        assert_eq!(thick.iter().filter(|&&t| t == -2).count(), 81 - 25);
+    }
+
+    #[test]
+    fn iter_non_default() {
+        let mut grid = testing_grid();
+        grid.set(V2i(3, 0), 2).unwrap();
+        grid.set(V2i(1, 4), -1).unwrap();
+        grid.set(V2i(2, 2), 0).unwrap();
+        let cells: Vec<(V2i, isize)> = grid.iter_non_default().map(|(pt, &t)| (pt, t)).collect();
+        assert_eq!(cells, vec![(V2i(3, 0), 2), (V2i(1, 4), -1)]);
+        assert_eq!(testing_grid().iter_non_default().count(), 0);
+    }
+
+    #[test]
+    fn border_openings() {
+        let grid = Grid::from_vec(
+            vec![
+                1, 0, 1, 1,
+                0, 0, 0, 1,
+                1, 0, 0, 1,
+                1, 1, 0, 1,
+            ], V2i(2, -1), V2i(4, 4),
+        ).unwrap();
+        assert_eq!(grid.border_openings(|&t| t == 0), 3);
+        assert_eq!(grid.border_openings(|&t| t == 1), 9);
+        assert_eq!(testing_grid().border_openings(|_| true), 16);
+
+        let line = Grid::from_vec(vec![0, 1, 0], V2i(0, 0), V2i(3, 1)).unwrap();
+        assert_eq!(line.border_openings(|&t| t == 0), 2);
+    }
+
+    #[test]
+    fn query() {
+        let grid = Grid::from_generator(|pt| (pt.0 * 7 + pt.1 * 3) % 5, V2i(-2, -2), V2i(6, 6)).unwrap();
+        let rect = R2i::origin_dim(V2i(-4, 0), V2i(5, 9));
+        let want = 1;
+        let found: Vec<(V2i, isize)> = grid.query(rect, |&t| t == want).map(|(pt, &t)| (pt, t)).collect();
+        let manual: Vec<(V2i, isize)> = grid.rect().iter()
+            .filter(|&pt| rect.contains(pt) && *grid.get(pt).unwrap() == 1)
+            .map(|pt| (pt, 1))
+            .collect();
+        assert!(!manual.is_empty());
+        assert_eq!(found, manual);
+
+        assert_eq!(grid.query(R2i::origin_dim(V2i(10, 10), V2i(2, 2)), |_| true).count(), 0);
+        assert_eq!(grid.query(R2i::origin_dim(V2i(-9, -9), V2i(20, 20)), |_| true).count(), 36);
+    }
+
+    #[test]
+    fn empty_rects() {
+        let grid = Grid::from_vec(
+            vec![
+                0, 0, 1, 0, 0, 0,
+                1, 0, 0, 0, 0, 1,
+                0, 0, 0, 0, 0, 0,
+                0, 1, 0, 0, 1, 0,
+            ], V2i(10, -2), V2i(6, 4),
+        ).unwrap();
+        let empty = |&t: &isize| t == 0;
+        assert_eq!(grid.largest_empty_rect(empty), Some(R2i::origin_dim(V2i(11, -1), V2i(4, 2))));
+
+        let rects = grid.maximal_empty_rects(empty);
+        for r in &rects {
+            assert!(r.iter().all(|pt| empty(grid.get(pt).unwrap())), "{:?} not empty", r);
+            for grown in &[
+                R2i::origin_opp(r.origin() - V2i::X, r.opp()),
+                R2i::origin_opp(r.origin() - V2i::Y, r.opp()),
+                R2i::origin_opp(r.origin(), r.opp() + V2i::X),
+                R2i::origin_opp(r.origin(), r.opp() + V2i::Y),
+            ] {
+                assert!(!grown.iter().all(|pt| grid.get(pt).is_ok_and(empty)), "{:?} not maximal", r);
+            }
+        }
+        let brute: usize = grid.rect().iter().filter(|&pt| empty(grid.get(pt).unwrap())).count();
+        let covered: std::collections::HashSet<V2i> = rects.iter().flat_map(R2i::iter).collect();
+        assert_eq!(covered.len(), brute);
+        let mut sorted = rects.clone();
+        sorted.sort_by_key(|r| (r.origin(), r.dim()));
+        sorted.dedup();
+        assert_eq!(sorted.len(), rects.len());
+        assert!(rects.contains(&R2i::origin_dim(V2i(10, 0), V2i(6, 1))));
+        assert!(rects.contains(&R2i::origin_dim(V2i(12, -1), V2i(2, 3))));
+
+        assert_eq!(testing_grid().largest_empty_rect(|&t| t != 0), None);
+        assert_eq!(testing_grid().largest_empty_rect(empty), Some(testing_grid().rect()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_validates() {
+        let grid = Grid::from_generator(|pt| pt.0 * pt.1, V2i(-1, 0), V2i(2, 3)).unwrap();
+        let json = serde_json::to_string(&grid).unwrap();
+        let back: Grid<isize> = serde_json::from_str(&json).expect("Round trip failed");
+        assert_eq!((back.rect(), back.into_vec()), (grid.rect(), grid.into_vec()));
+
+        let short = r#"{"array":[1,2,3],"origin":[0,0],"dim":[2,2]}"#;
+        assert!(serde_json::from_str::<Grid<isize>>(short).is_err());
+        let negative = r#"{"array":[],"origin":[0,0],"dim":[-1,0]}"#;
+        assert!(serde_json::from_str::<Grid<isize>>(negative).is_err());
+    }
 }
@@ -0,0 +1,97 @@
+use crate::*;
+use super::{Error, row_major_index};
+
+/* Cells of 4 bits (0..=15), two to a byte: the even cell of each pair in the low nibble. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NibbleGrid {
+    bytes: Box<[u8]>,
+    origin: V2i,
+    dim: V2i,
+}
+
+impl NibbleGrid {
+    pub const MAX: u8 = 0xf;
+
+    /* All cells start at 0 */
+    pub fn new(origin: V2i, dim: V2i) -> Result<NibbleGrid, Error> {
+        if !dim.is_q1() {
+            return Err(Error::NegativeDim(dim));
+        }
+        let cells = dim.0 as usize * dim.1 as usize;
+        Ok(NibbleGrid {
+            bytes: vec![0u8; cells.div_ceil(2)].into_boxed_slice(),
+            origin, dim,
+        })
+    }
+
+    pub fn origin(&self) -> V2i { self.origin }
+    pub fn dim(&self) -> V2i { self.dim }
+    pub fn rect(&self) -> R2i { R2i::origin_dim(self.origin, self.dim) }
+
+    pub fn contains(&self, v: V2i) -> bool { self.rect().contains(v) }
+
+    pub fn get(&self, v: V2i) -> Result<u8, Error> {
+        let i = row_major_index(self.rect(), v)?;
+        Ok((self.bytes[i / 2] >> (4 * (i % 2))) & NibbleGrid::MAX)
+    }
+
+    /* Leaves the grid untouched if v is out of bounds or value doesn't fit in a nibble. */
+    pub fn set(&mut self, v: V2i, value: u8) -> Result<(), Error> {
+        if value > NibbleGrid::MAX {
+            return Err(Error::ValueOutOfRange(value));
+        }
+        let i = row_major_index(self.rect(), v)?;
+        let shift = 4 * (i % 2);
+        let byte = &mut self.bytes[i / 2];
+        *byte = (*byte & !(NibbleGrid::MAX << shift)) | (value << shift);
+        Ok(())
+    }
+
+    /* Row-major, as Grid::iter */
+    pub fn iter(&self) -> impl Iterator<Item=u8> + '_ {
+        let cells = self.dim.0 as usize * self.dim.1 as usize;
+        (0..cells).map(move |i| (self.bytes[i / 2] >> (4 * (i % 2))) & NibbleGrid::MAX)
+    }
+
+    /* The packed storage, two cells per byte */
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packing() {
+        let mut grid = NibbleGrid::new(V2i(-1, 2), V2i(3, 3)).expect("Creating grid failed");
+        assert_eq!(grid.bytes().len(), 5);
+        for (i, pt) in grid.rect().iter().enumerate() {
+            grid.set(pt, (i as u8 * 7) % 16).unwrap();
+        }
+        for (i, pt) in grid.rect().iter().enumerate() {
+            assert_eq!(grid.get(pt).unwrap(), (i as u8 * 7) % 16, "at {:?}", pt);
+        }
+        assert_eq!(grid.iter().collect::<Vec<_>>(), (0..9).map(|i| (i * 7) % 16).collect::<Vec<u8>>());
+
+        /* Cells 1 and 2 straddle a byte boundary; 2 and 3 share one */
+        grid.set(V2i(0, 2), 15).unwrap();
+        grid.set(V2i(1, 2), 0).unwrap();
+        assert_eq!(grid.get(V2i(-1, 2)).unwrap(), 0);
+        assert_eq!(grid.get(V2i(0, 2)).unwrap(), 15);
+        assert_eq!(grid.get(V2i(1, 2)).unwrap(), 0);
+        assert_eq!(grid.get(V2i(-1, 3)).unwrap(), 5);
+    }
+
+    #[test]
+    fn rejects_bad_writes() {
+        let mut grid = NibbleGrid::new(V2i(0, 0), V2i(2, 2)).unwrap();
+        grid.set(V2i(1, 1), 9).unwrap();
+        assert!(matches!(grid.set(V2i(1, 1), 16), Err(Error::ValueOutOfRange(16))));
+        assert_eq!(grid.get(V2i(1, 1)).unwrap(), 9);
+        assert!(matches!(grid.set(V2i(2, 0), 1), Err(Error::OutOfBounds { axis: Some(Axis::X), .. })));
+        assert!(grid.get(V2i(0, -1)).is_err());
+        assert!(matches!(NibbleGrid::new(V2i(0, 0), V2i(-1, 2)), Err(Error::NegativeDim(_))));
+    }
+}
@@ -1,13 +1,21 @@
 use crate::*;
-use super::{Grid, region::{Region, RegionConfig}};
+use super::{Grid, region::Region};
 
 use std::cmp::{Reverse, Ordering};
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, hash_map::Entry};
+use std::hash::Hash;
 
 pub trait Traversable: {
     fn can_pass(&self) -> bool;
 }
 
+/* Cost of stepping from one cell onto an adjacent one, dir being the step taken; None forbids the
+ * move. Costs must be at least 1: Grid::path_edges uses the topology's distance in cells as its
+ * heuristic, which overestimates (and so may miss the shortest path) when edges are cheaper. */
+pub trait EdgeCost {
+    fn edge_cost(from: &Self, to: &Self, dir: V2i) -> Option<usize>;
+}
+
 pub trait Neighbors<T>: Sized {
     fn neighbors(&self, nb: &mut Vec<Self>);
 }
@@ -16,109 +24,308 @@ pub trait Neighbors<T>: Sized {
 pub struct L1;
 impl Neighbors<L1> for V2i {
     fn neighbors(&self, nb: &mut Vec<V2i>) {
-        nb.push(*self + V2i(1, 0));
-        nb.push(*self + V2i(0, 1));
-        nb.push(*self + V2i(-1, 0));
-        nb.push(*self + V2i(0, -1));
+        nb.extend_from_slice(&self.neighbors4());
     }
 }
 
+impl Metric for L1 {
+    fn norm(v: V2i) -> Vi { v.l1() }
+}
+
 #[derive(Debug)]
 pub struct Linf;
 impl Neighbors<Linf> for V2i {
     fn neighbors(&self, nb: &mut Vec<V2i>) {
-        nb.push(*self + V2i(1, 0));
-        nb.push(*self + V2i(1, 1));
-        nb.push(*self + V2i(0, 1));
-        nb.push(*self + V2i(-1, 1));
-        nb.push(*self + V2i(-1, 0));
-        nb.push(*self + V2i(-1, -1));
-        nb.push(*self + V2i(0, -1));
-        nb.push(*self + V2i(1, -1));
+        nb.extend_from_slice(&self.neighbors8());
+    }
+}
+
+impl Metric for Linf {
+    fn norm(v: V2i) -> Vi { v.linf() }
+}
+
+/* Axial hex coordinates: V2i(q, r), with the third cube coordinate s = -q - r. The six neighbors
+ * are (+1, 0), (+1, -1), (0, -1), (-1, 0), (-1, +1), (0, +1), i.e. pointy-top hexes where +r runs
+ * down-right. */
+#[derive(Debug)]
+pub struct Hex;
+impl Neighbors<Hex> for V2i {
+    fn neighbors(&self, nb: &mut Vec<V2i>) {
+        nb.extend([
+            V2i(1, 0), V2i(1, -1), V2i(0, -1),
+            V2i(-1, 0), V2i(-1, 1), V2i(0, 1),
+        ].iter().map(|&d| *self + d));
     }
 }
 
+impl Metric for Hex {
+    fn norm(v: V2i) -> Vi { v.0.abs().max(v.1.abs()).max((v.0 + v.1).abs()) }
+}
+
+/* Euclidean distance rounded up; a metric only, with no neighborhood of its own. */
+#[derive(Debug)]
+pub struct L2;
+impl Metric for L2 {
+    fn norm(v: V2i) -> Vi { v.l2_ceil() }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     Disconnected,
 }
 
+/* Search effort: nodes popped from the open set, the open set's peak size, and the number of cells
+ * in the resulting path (0 if none was found). */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathStats {
+    pub expanded: usize,
+    pub max_open: usize,
+    pub length: usize,
+}
+
 #[derive(Debug)]
-struct State {
-    node: V2i,
+struct State<N> {
+    node: N,
     cost: usize,
 }
 
-impl PartialEq for State {
-    fn eq(&self, other: &State) -> bool { self.cost == other.cost }
+impl<N> PartialEq for State<N> {
+    fn eq(&self, other: &State<N>) -> bool { self.cost == other.cost }
 }
 
-impl Eq for State {}
+impl<N> Eq for State<N> {}
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &State) -> Option<Ordering> { Some(self.cmp(other)) }
+impl<N> PartialOrd for State<N> {
+    fn partial_cmp(&self, other: &State<N>) -> Option<Ordering> { Some(self.cmp(other)) }
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &State) -> Ordering { self.cost.cmp(&other.cost) }
+impl<N> Ord for State<N> {
+    fn cmp(&self, other: &State<N>) -> Ordering { self.cost.cmp(&other.cost) }
 }
 
-pub fn path<N, A>(start: V2i, goal: V2i, mut allow: A) -> Result<Vec<V2i>, Error>
+/* Returns the goal reached and the back-links toward start. */
+fn search<N, FN, FH, FG>(start: N, mut is_goal: FG, mut neighbors: FN, mut heuristic: FH, stats: &mut PathStats) -> Option<(N, HashMap<N, N>)>
     where
-        V2i: Neighbors<N>,
-        A: FnMut(V2i) -> bool
+        N: Hash + Eq + Clone,
+        FN: FnMut(&N) -> Vec<(N, usize)>,
+        FH: FnMut(&N) -> usize,
+        FG: FnMut(&N) -> bool
 {
     let mut back = HashMap::new();
     let mut cost = HashMap::new();
     let mut open = BinaryHeap::new();
-    let mut neighbors = Vec::new();
     
-    open.push(Reverse(State { node: start, cost: 0 }));
+    open.push(Reverse(State { node: start.clone(), cost: 0 }));
     cost.insert(start, 0usize);
 
     while let Some(visit) = open.pop() {
-        #[cfg(test)] println!("visit: {:?}", visit);
-
+        stats.expanded += 1;
         let current = visit.0;
-        if current.node == goal {
-            let mut current = goal;  // NB: shadowed
-            let mut path = Vec::new();
-            loop {
-                #[cfg(test)] println!("current: {:?}", current);
-
-                path.push(current);
-                if let Some(next) = back.get(&current) {
-                    current = *next;
-                } else {
-                    path.reverse();
-                    return Ok(path);
-                }
+        if is_goal(&current.node) {
+            return Some((current.node, back));
+        }
+
+        for (neigh, step) in neighbors(&current.node) {
+            let est = cost.get(&current.node).unwrap() + step;
+            if cost.get(&neigh).is_none_or(|&c| est < c) {
+                cost.insert(neigh.clone(), est);
+                back.insert(neigh.clone(), current.node.clone());
+                let h = heuristic(&neigh);
+                open.push(Reverse(State { node: neigh, cost: est + h }));
             }
         }
+        stats.max_open = stats.max_open.max(open.len());
+    }
 
-        current.node.neighbors(&mut neighbors);  // NB: Implicitly using the implementation for N
+    None
+}
 
-        for neigh in neighbors.drain(..) {
-            if !allow(neigh) {
-                continue;
-            }
+/* A* over an arbitrary graph; neighbors yields (node, step cost) pairs, and heuristic must not
+ * overestimate the remaining cost for the result to be optimal. */
+pub fn astar<N, FN, FH, FG>(start: N, is_goal: FG, neighbors: FN, heuristic: FH) -> Result<Vec<N>, Error>
+    where
+        N: Hash + Eq + Clone,
+        FN: FnMut(&N) -> Vec<(N, usize)>,
+        FH: FnMut(&N) -> usize,
+        FG: FnMut(&N) -> bool
+{
+    let (mut current, back) = search(start, is_goal, neighbors, heuristic, &mut PathStats::default()).ok_or(Error::Disconnected)?;
+    let mut path = vec![current.clone()];
+    while let Some(next) = back.get(&current) {
+        current = next.clone();
+        path.push(current.clone());
+    }
+    path.reverse();
+    Ok(path)
+}
 
+/* Walks the search's back-links from the cell reached to start, so it yields the path in reverse
+ * (goal first, start last). */
+#[derive(Debug, Clone)]
+pub struct PathIter {
+    back: HashMap<V2i, V2i>,
+    current: Option<V2i>,
+}
 
-            let est = cost.get(&current.node).unwrap() + 1;  // NB: const 1 cost per traversal assumed
-            if !cost.contains_key(&neigh) || est < *cost.get(&neigh).unwrap() {
-                cost.insert(neigh, est);
-                back.insert(neigh, current.node);
-                open.push(Reverse(State { node: neigh, cost: est + (neigh - goal).l1() as usize }));
-            }
+impl PathIter {
+    /* The whole path in forward order, as path returns it. */
+    fn into_path(self) -> Vec<V2i> {
+        let mut path: Vec<V2i> = self.collect();
+        path.reverse();
+        path
+    }
+}
+
+impl Iterator for PathIter {
+    type Item = V2i;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cur = self.current?;
+        self.current = self.back.get(&cur).copied();
+        Some(cur)
+    }
+}
+
+/* As path, but yielding the cells from goal back to start. The search itself is still eager; only
+ * the walk along its back-links is lazy. */
+pub fn path_iter<N, A>(start: V2i, goal: V2i, allow: A) -> Result<PathIter, Error>
+    where
+        N: Metric,
+        V2i: Neighbors<N>,
+        A: FnMut(V2i) -> bool
+{
+    search_grid::<N, A>(start, goal, 0, allow, &mut PathStats::default())
+}
+
+/* A* over V2i under topology N with unit step costs, stopping at the first cell within `within` of
+ * goal under N (goal itself when within is 0). */
+fn search_grid<N, A>(start: V2i, goal: V2i, within: Vi, mut allow: A, stats: &mut PathStats) -> Result<PathIter, Error>
+    where
+        N: Metric,
+        V2i: Neighbors<N>,
+        A: FnMut(V2i) -> bool
+{
+    let mut neighbors = Vec::new();
+    let (reached, back) = search(
+        start,
+        |&node| N::dist(node, goal) <= within,
+        |node: &V2i| {
+            node.neighbors(&mut neighbors);  // NB: Implicitly using the implementation for N
+            neighbors.drain(..).filter(|&n| allow(n)).map(|n| (n, 1)).collect()  // NB: const 1 cost per traversal assumed
+        },
+        |&node| (N::dist(node, goal) - within).max(0) as usize,
+        stats,
+    ).ok_or(Error::Disconnected)?;
+    Ok(PathIter { back, current: Some(reached) })
+}
+
+pub fn path<N, A>(start: V2i, goal: V2i, allow: A) -> Result<Vec<V2i>, Error>
+    where
+        N: Metric,
+        V2i: Neighbors<N>,
+        A: FnMut(V2i) -> bool
+{
+    path_iter::<N, A>(start, goal, allow).map(PathIter::into_path)
+}
+
+/* As path, but ending at the first cell found within `within` of goal under N (an admissible
+ * search, so the path is still a shortest one to any such cell). */
+pub fn path_within<N, A>(start: V2i, goal: V2i, within: Vi, allow: A) -> Result<Vec<V2i>, Error>
+    where
+        N: Metric,
+        V2i: Neighbors<N>,
+        A: FnMut(V2i) -> bool
+{
+    search_grid::<N, A>(start, goal, within, allow, &mut PathStats::default()).map(PathIter::into_path)
+}
+
+/* As path, also reporting how much work the search did. */
+pub fn path_with_stats<N, A>(start: V2i, goal: V2i, allow: A) -> (Result<Vec<V2i>, Error>, PathStats)
+    where
+        N: Metric,
+        V2i: Neighbors<N>,
+        A: FnMut(V2i) -> bool
+{
+    let mut stats = PathStats::default();
+    let res: Result<Vec<V2i>, Error> = search_grid::<N, A>(start, goal, 0, allow, &mut stats).map(PathIter::into_path);
+    stats.length = res.as_ref().map_or(0, Vec::len);
+    (res, stats)
+}
+
+/* The step between each consecutive pair of cells, so the result is one shorter than path. */
+pub fn to_directions(path: &[V2i]) -> Vec<V2i> {
+    path.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+/* Turns every cell clockwise (as V2i::rot_cw) about pivot, quarter_turns times. */
+pub fn rotate(path: &[V2i], pivot: V2i, quarter_turns: u32) -> Vec<V2i> {
+    path.iter().map(|&pt| {
+        let mut d = pt - pivot;
+        for _ in 0..quarter_turns % 4 {
+            d = d.rot_cw();
+        }
+        pivot + d
+    }).collect()
+}
+
+/* As to_directions, but None if any step is not a single unit move (as from L1 or Linf). */
+pub fn to_compass(path: &[V2i]) -> Option<Vec<Direction>> {
+    path.windows(2).map(|w| Direction::from_step(w[1] - w[0])).collect()
+}
+
+/* Memoizes successful searches by (start, goal); failures are always recomputed. */
+#[derive(Debug, Clone, Default)]
+pub struct PathCache {
+    paths: HashMap<(V2i, V2i), Vec<V2i>>,
+}
+
+impl PathCache {
+    pub fn new() -> PathCache {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn get_or_compute<N, A>(&mut self, start: V2i, goal: V2i, allow: A) -> Result<&[V2i], Error>
+        where
+            N: Metric,
+            V2i: Neighbors<N>,
+            A: FnMut(V2i) -> bool
+    {
+        match self.paths.entry((start, goal)) {
+            Entry::Occupied(e) => Ok(e.into_mut()),
+            Entry::Vacant(e) => Ok(e.insert(path::<N, A>(start, goal, allow)?)),
         }
     }
 
-    Err(Error::Disconnected)
+    pub fn invalidate_all(&mut self) {
+        self.paths.clear();
+    }
+
+    /* Drops every path passing through rect. */
+    pub fn invalidate_near(&mut self, rect: R2i) {
+        self.paths.retain(|_, p| !p.iter().any(|&pt| rect.contains(pt)));
+    }
 }
 
 impl<T: Traversable> Grid<T> {
+    /* Searches rings of increasing N-distance; within a ring, the first passable cell in
+     * row-major order wins. */
+    pub fn nearest_passable<N: Metric>(&self, from: V2i, max_radius: Vi) -> Option<V2i> {
+        (0..=max_radius).find_map(|r|
+            raster::ring::<N>(from, r).find(|&pt| self.get(pt).is_ok_and(|t| t.can_pass()))
+        )
+    }
+
     pub fn path<N>(&self, start: V2i, goal: V2i, radius: Option<usize>) -> Result<Vec<V2i>, Error>
         where
+            N: Metric,
             V2i: Neighbors<N>
     {
         let limsq = radius.map(|x| x*x);
@@ -137,9 +344,42 @@ impl<T: Traversable> Grid<T> {
     }
 }
 
+impl<T: EdgeCost> Grid<T> {
+    /* NB: The heuristic assumes every move costs at least 1 (see EdgeCost) */
+    pub fn path_edges<N>(&self, start: V2i, goal: V2i) -> Result<Vec<V2i>, Error>
+        where
+            N: Metric,
+            V2i: Neighbors<N>
+    {
+        if !self.contains(start) || !self.contains(goal) {
+            return Err(Error::Disconnected);
+        }
+
+        let mut neighbors = Vec::new();
+        astar(
+            start,
+            |&node| node == goal,
+            |&node| {
+                let from = match self.get(node) {
+                    Ok(from) => from,
+                    Err(_) => return Vec::new(),
+                };
+                node.neighbors(&mut neighbors);
+                neighbors.drain(..).filter_map(|n|
+                    self.get(n).ok()
+                        .and_then(|to| T::edge_cost(from, to, n - node))
+                        .map(|c| (n, c))
+                ).collect()
+            },
+            |&node| N::dist(node, goal) as usize,
+        )
+    }
+}
+
 impl<T: Traversable + Default> Region<T> {
     pub fn path<N>(&self, start: V2i, goal: V2i, radius: Option<usize>) -> Result<Vec<V2i>, Error>
         where
+            N: Metric,
             V2i: Neighbors<N>
     {
         let limsq = radius.map(|x| x*x);
@@ -159,6 +399,7 @@ impl<T: Traversable + Default> Region<T> {
 
     pub fn path_mut<N>(&mut self, start: V2i, goal: V2i, radius: Option<usize>) -> Result<Vec<V2i>, Error>
         where
+            N: Metric,
             V2i: Neighbors<N>
     {
         let limsq = radius.map(|x| x*x);
@@ -176,11 +417,26 @@ impl<T: Traversable + Default> Region<T> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::grid::region::RegionConfig;
 
     impl Traversable for isize {
         fn can_pass(&self) -> bool { *self == 0 }
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Height(isize);
+
+    /* Drops of any height are cheap; climbing one level is costly; climbing more is impossible */
+    impl EdgeCost for Height {
+        fn edge_cost(from: &Height, to: &Height, _dir: V2i) -> Option<usize> {
+            match to.0 - from.0 {
+                d if d > 1 => None,
+                1 => Some(3),
+                _ => Some(1),
+            }
+        }
+    }
+
     fn testing_grid() -> Grid<isize> {
         Grid::from_vec(
             vec![
@@ -240,6 +496,195 @@ mod test {
     }
     */
 
+    #[test]
+    fn asymmetric_edge_costs() {
+        let grid = Grid::from_vec(
+            vec![
+                2, 0, 2,
+                2, 1, 2,
+                2, 2, 2,
+            ].into_iter().map(Height).collect(), V2i(0, 0), V2i(3, 3),
+        ).unwrap();
+
+        let down = grid.path_edges::<L1>(V2i(0, 0), V2i(1, 0)).expect("No path");
+        assert_eq!(down, vec![V2i(0, 0), V2i(1, 0)]);
+        let up = grid.path_edges::<L1>(V2i(1, 0), V2i(0, 0)).expect("No path");
+        println!("up: {:?}", up);
+        assert_eq!(up, vec![V2i(1, 0), V2i(1, 1), V2i(0, 1), V2i(0, 0)]);
+
+        let cliff = Grid::from_vec(vec![Height(3), Height(0)], V2i(0, 0), V2i(2, 1)).unwrap();
+        assert!(cliff.path_edges::<L1>(V2i(0, 0), V2i(1, 0)).is_ok());
+        assert_eq!(cliff.path_edges::<L1>(V2i(1, 0), V2i(0, 0)), Err(Error::Disconnected));
+        assert_eq!(cliff.path_edges::<L1>(V2i(-1, 0), V2i(0, 0)), Err(Error::Disconnected));
+        assert_eq!(cliff.path_edges::<L1>(V2i(0, 0), V2i(5, 0)), Err(Error::Disconnected));
+    }
+
+    #[test]
+    fn finds_hex_path() {
+        let open: Grid<isize> = Grid::from_default(V2i(-6, -6), V2i(13, 13)).unwrap();
+        for &goal in &[V2i(4, 0), V2i(3, -5), V2i(-4, 4), V2i(-2, -3), V2i(5, 1)] {
+            let path = open.path::<Hex>(V2i(0, 0), goal, None).expect("No path");
+            println!("path: {:?}", path);
+            assert_eq!(path.len() as Vi - 1, Hex::dist(V2i(0, 0), goal));
+            for pair in path.windows(2) {
+                assert_eq!(Hex::dist(pair[0], pair[1]), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn iter_matches_path() {
+        let grid = testing_grid();
+        let allow = |pos| grid.get(pos).is_ok_and(|t| t.can_pass());
+        for &(a, b) in &[(V2i(1, 3), V2i(3, 3)), (V2i(1, 1), V2i(1, 1)), (V2i(3, 1), V2i(1, 2))] {
+            let eager = path::<L1, _>(a, b, allow).expect("No path");
+            let mut lazy: Vec<_> = path_iter::<L1, _>(a, b, allow).expect("No path").collect();
+            println!("path: {:?}", lazy);
+            assert_eq!(lazy.first(), Some(&b));
+            assert_eq!(lazy.last(), Some(&a));
+            lazy.reverse();
+            assert_eq!(eager, lazy);
+        }
+        assert!(path_iter::<L1, _>(V2i(1, 1), V2i(0, 0), allow).is_err());
+    }
+
+    #[test]
+    fn directions() {
+        let grid = testing_grid();
+        let allow = |pos| grid.get(pos).is_ok_and(|t| t.can_pass());
+        let found = path::<L1, _>(V2i(1, 3), V2i(3, 1), allow).expect("No path");
+        let steps = to_directions(&found);
+        assert_eq!(steps.len(), found.len() - 1);
+        assert_eq!(steps.iter().fold(V2i::ZERO, |a, &b| a + b), V2i(3, 1) - V2i(1, 3));
+
+        let known = [V2i(0, 0), V2i(1, 0), V2i(1, 1), V2i(0, 2), V2i(0, 1)];
+        assert_eq!(to_directions(&known), vec![V2i(1, 0), V2i(0, 1), V2i(-1, 1), V2i(0, -1)]);
+        assert_eq!(to_compass(&known), Some(vec![Direction::E, Direction::S, Direction::SW, Direction::N]));
+        assert_eq!(to_compass(&[V2i(0, 0), V2i(2, 0)]), None);
+        assert!(to_directions(&[V2i(4, 4)]).is_empty());
+        assert!(to_directions(&[]).is_empty());
+    }
+
+    #[test]
+    fn stops_within_range() {
+        let grid = testing_grid();
+        let allow = |pos| grid.get(pos).is_ok_and(|t| t.can_pass());
+        let (start, goal) = (V2i(1, 3), V2i(3, 1));
+        let full = path::<L1, _>(start, goal, allow).expect("No path");
+        let near = path_within::<L1, _>(start, goal, 1, allow).expect("No path");
+        assert_eq!(near.first(), Some(&start));
+        assert_eq!(L1::dist(*near.last().unwrap(), goal), 1);
+        assert_eq!(near.len(), full.len() - 1);
+
+        assert_eq!(path_within::<L1, _>(start, goal, 0, allow).unwrap(), full);
+        assert_eq!(path_within::<L1, _>(start, goal, 10, allow).unwrap(), vec![start]);
+    }
+
+    #[test]
+    fn rotate_about_start() {
+        let straight = [V2i(2, 1), V2i(3, 1), V2i(4, 1)];
+        assert_eq!(rotate(&straight, V2i(2, 1), 1), vec![V2i(2, 1), V2i(2, 2), V2i(2, 3)]);
+        assert_eq!(rotate(&straight, V2i(2, 1), 2), vec![V2i(2, 1), V2i(1, 1), V2i(0, 1)]);
+        assert_eq!(rotate(&straight, V2i(2, 1), 3), vec![V2i(2, 1), V2i(2, 0), V2i(2, -1)]);
+        assert_eq!(rotate(&straight, V2i(2, 1), 4), straight.to_vec());
+
+        let bent = [V2i(0, 0), V2i(1, 0), V2i(1, 1)];
+        assert_eq!(rotate(&bent, V2i(1, 1), 1), vec![V2i(2, 0), V2i(2, 1), V2i(1, 1)]);
+        assert_eq!(to_directions(&rotate(&bent, V2i(5, 5), 1)), to_directions(&bent).iter().map(|d| d.rot_cw()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn path_stats() {
+        let grid = testing_grid();
+        let allow = |pos| grid.get(pos).is_ok_and(|t| t.can_pass());
+        let (res, stats) = path_with_stats::<L1, _>(V2i(1, 3), V2i(3, 1), allow);
+        let found = res.expect("No path");
+        assert_eq!(found, path::<L1, _>(V2i(1, 3), V2i(3, 1), allow).unwrap());
+        assert_eq!(stats.length, found.len());
+        assert!(stats.expanded >= found.len());
+        assert!(stats.max_open > 0);
+
+        let (res, stats) = path_with_stats::<Linf, _>(V2i(1, 3), V2i(3, 1), allow);
+        assert_eq!(res.expect("No path"), path::<Linf, _>(V2i(1, 3), V2i(3, 1), allow).unwrap());
+        assert_eq!(stats.length, 4);  // NB: Around the wall at (2, 2)
+
+        let (res, stats) = path_with_stats::<L1, _>(V2i(1, 1), V2i(0, 0), allow);
+        assert!(res.is_err());
+        assert_eq!(stats.length, 0);
+        assert!(stats.expanded > 0);
+    }
+
+    #[test]
+    fn astar_on_graph() {
+        let roads: &[(&str, &str, usize)] = &[
+            ("ford", "mill", 4), ("ford", "keep", 9), ("mill", "abbey", 3),
+            ("abbey", "keep", 1), ("keep", "port", 6), ("mill", "port", 20),
+        ];
+        let neighbors = |town: &&str| roads.iter().filter_map(|&(a, b, c)|
+            if a == *town { Some((b, c)) } else if b == *town { Some((a, c)) } else { None }
+        ).collect();
+
+        let res = astar("ford", |t| *t == "port", neighbors, |_| 0);
+        println!("path: {:?}", res);
+        assert_eq!(res, Ok(vec!["ford", "mill", "abbey", "keep", "port"]));
+        assert_eq!(astar("ford", |t| *t == "moor", neighbors, |_| 0), Err(Error::Disconnected));
+    }
+
+    #[test]
+    fn nearest_passable() {
+        let grid: Grid<isize> = Grid::from_vec(
+            vec![
+                0, 0, 0, 0, 0,
+                0, 1, 1, 1, 0,
+                0, 1, 1, 1, 0,
+                0, 1, 1, 1, 0,
+                0, 0, 0, 0, 0,
+            ], V2i(0, 0), V2i(5, 5),
+        ).unwrap();
+        let found = grid.nearest_passable::<Linf>(V2i(2, 2), 3).expect("Nothing found");
+        assert_eq!((found - V2i(2, 2)).linf(), 2);
+        assert_eq!(found, V2i(0, 0));
+        let found = grid.nearest_passable::<L1>(V2i(2, 2), 3).expect("Nothing found");
+        assert_eq!(found, V2i(2, 0));
+        assert_eq!(grid.nearest_passable::<Linf>(V2i(2, 2), 1), None);
+        assert_eq!(grid.nearest_passable::<Linf>(V2i(4, 4), 1), Some(V2i(4, 4)));
+    }
+
+    #[test]
+    fn path_cache() {
+        let grid = testing_grid();
+        let calls = std::cell::Cell::new(0);
+        let allow = |pos| {
+            calls.set(calls.get() + 1);
+            grid.get(pos).is_ok_and(|t| t.can_pass())
+        };
+        let mut cache = PathCache::new();
+
+        let first = cache.get_or_compute::<L1, _>(V2i(1, 3), V2i(3, 3), allow).expect("No path").to_vec();
+        let used = calls.get();
+        assert!(used > 0);
+        let second = cache.get_or_compute::<L1, _>(V2i(1, 3), V2i(3, 3), allow).expect("No path").to_vec();
+        assert_eq!(calls.get(), used);
+        assert_eq!(first, second);
+
+        cache.get_or_compute::<L1, _>(V2i(1, 1), V2i(1, 2), allow).expect("No path");
+        assert_eq!(cache.len(), 2);
+        cache.invalidate_near(R2i::origin_dim(V2i(3, 1), V2i(1, 1)));
+        assert_eq!(cache.len(), 1);
+        cache.invalidate_all();
+        assert!(cache.is_empty());
+        cache.get_or_compute::<L1, _>(V2i(1, 3), V2i(3, 3), allow).expect("No path");
+        assert!(calls.get() > used);
+    }
+
+    #[test]
+    fn works_on_wrapped_grid() {
+        let reg = testing_grid().into_region().expect("Failed to wrap grid");
+        let path = reg.path::<L1>(V2i(1, 3), V2i(3, 3), None).expect("No path");
+        assert_eq!(path.len(), 7);
+        assert!(reg.path::<L1>(V2i(1, 3), V2i(5, 3), None).is_err());
+    }
+
     #[test]
     fn works_on_regions() {
         let mut reg: Region<isize> = RegionConfig::default().build().unwrap();
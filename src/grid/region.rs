@@ -3,27 +3,39 @@ use crate::*;
 use crate::grid::Grid;
 
 use std::fmt::{self, Debug};
-use std::collections::HashMap;
+use std::collections::{HashMap, hash_map::Entry};
 use std::marker::PhantomData;
 
+#[cfg(feature = "serde")]
+use std::{fs, io::{self, Write}, path::Path};
+
 /* Arguments: Invoking point, Region coordinate, Grid origin, Grid dim */
-type GridGen<T> = Box<dyn FnMut(V2i, V2i, V2i, V2i) -> Grid<T>>;
+pub type GridGen<T> = Box<dyn FnMut(V2i, V2i, V2i, V2i) -> Grid<T>>;
+
+/* Argument: Region coordinate of the chunk */
+pub type ChunkHook = Box<dyn FnMut(V2i)>;
 
 pub struct Region<T> {
     grid_size: V2i,
     grids: HashMap<V2i, Grid<T>>,
     grid_gen: Option<GridGen<T>>,
+    on_load: Option<ChunkHook>,
+    on_unload: Option<ChunkHook>,
 }
 
 pub struct RegionConfig<T> {
     grid_size: V2i,
     grid_gen: Option<GridGen<T>>,
+    on_load: Option<ChunkHook>,
+    on_unload: Option<ChunkHook>,
     _t: PhantomData<T>,
 }
 
 #[derive(Debug)]
 pub enum Error {
     NonPositiveDim(V2i),
+    MismatchedGridSize(V2i, V2i),
+    MisalignedGrid(V2i, V2i),
 }
 
 impl<T: Debug> Debug for Region<T> {
@@ -35,11 +47,27 @@ impl<T: Debug> Debug for Region<T> {
     }
 }
 
+/* The generator and hooks can't be cloned, so the clone has none; it falls back to default-filled
+ * chunks. */
+impl<T: Clone> Clone for Region<T> {
+    fn clone(&self) -> Region<T> {
+        Region {
+            grid_size: self.grid_size,
+            grids: self.grids.clone(),
+            grid_gen: None,
+            on_load: None,
+            on_unload: None,
+        }
+    }
+}
+
 impl<T> Default for RegionConfig<T> {
     fn default() -> RegionConfig<T> {
         RegionConfig {
             grid_size: V2i(32, 32),
             grid_gen: None,
+            on_load: None,
+            on_unload: None,
             _t: PhantomData,
         }
     }
@@ -54,6 +82,29 @@ impl<T> RegionConfig<T> {
         RegionConfig { grid_gen, ..self }
     }
 
+    pub fn with_generator<F>(self, grid_gen: F) -> RegionConfig<T>
+        where
+            F: FnMut(V2i, V2i, V2i, V2i) -> Grid<T> + 'static
+    {
+        self.with_grid_gen(Some(Box::new(grid_gen)))
+    }
+
+    /* Called after a chunk is first created by get_grid_mut */
+    pub fn on_load<F>(self, hook: F) -> RegionConfig<T>
+        where
+            F: FnMut(V2i) + 'static
+    {
+        RegionConfig { on_load: Some(Box::new(hook)), ..self }
+    }
+
+    /* Called after a loaded chunk is removed by unload */
+    pub fn on_unload<F>(self, hook: F) -> RegionConfig<T>
+        where
+            F: FnMut(V2i) + 'static
+    {
+        RegionConfig { on_unload: Some(Box::new(hook)), ..self }
+    }
+
     pub fn build(self) -> Result<Region<T>, Error> {
         if !self.grid_size.is_strict_q1() {
             return Err(Error::NonPositiveDim(self.grid_size));
@@ -62,10 +113,30 @@ impl<T> RegionConfig<T> {
             grid_size: self.grid_size,
             grids: HashMap::new(),
             grid_gen: self.grid_gen,
+            on_load: self.on_load,
+            on_unload: self.on_unload,
         })
     }
 }
 
+impl<T> Grid<T> {
+    /* Wraps self as the only loaded chunk of a Region whose grid_size is self's dim; the origin
+     * must therefore be a multiple of the dim. */
+    pub fn into_region(self) -> Result<Region<T>, Error> {
+        let (origin, dim) = (self.origin, self.dim);
+        if !dim.is_strict_q1() {
+            return Err(Error::NonPositiveDim(dim));
+        }
+        if origin.rem_euclid(dim) != V2i::ZERO {
+            return Err(Error::MisalignedGrid(origin, dim));
+        }
+
+        let mut region = RegionConfig::default().with_grid_size(dim).build()?;
+        region.grids.insert(origin.div_euclid(dim), self);
+        Ok(region)
+    }
+}
+
 impl<T: Default> Region<T> {
     pub fn grid_size(&self) -> V2i {
         self.grid_size
@@ -86,26 +157,49 @@ impl<T: Default> Region<T> {
     pub fn get_grid_mut(&mut self, v: V2i) -> &mut Grid<T> {
         let gi = self.get_grid_index(v);
         let gs = self.grid_size;
-        let gg = self.grid_gen.as_mut();
-        self.grids.entry(gi).or_insert_with(||
-            match gg {
-                Some(gen) => gen(v, gi, gi * gs, gs),
-                None => Grid::from_default(
-                    gi * gs,
-                    gs
-                ).unwrap(),
-            }
-        )
+        match self.grids.entry(gi) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let grid = match self.grid_gen.as_mut() {
+                    Some(gen) => gen(v, gi, gi * gs, gs),
+                    None => Grid::from_default(
+                        gi * gs,
+                        gs
+                    ).unwrap(),
+                };
+                if let Some(hook) = self.on_load.as_mut() {
+                    hook(gi);
+                }
+                e.insert(grid)
+            },
+        }
     }
 
     pub fn get_grid(&self, v: V2i) -> Option<&Grid<T>> {
         self.grids.get(&self.get_grid_index(v))
     }
 
+    pub fn unload(&mut self, index: V2i) -> Option<Grid<T>> {
+        let grid = self.grids.remove(&index);
+        if grid.is_some() {
+            if let Some(hook) = self.on_unload.as_mut() {
+                hook(index);
+            }
+        }
+        grid
+    }
+
     pub fn get(&self, v: V2i) -> Option<&T> {
         self.get_grid(v).map(|g| g.get(v).unwrap())
     }
 
+    pub fn get_copied(&self, v: V2i, default: T) -> T
+        where
+            T: Copy
+    {
+        self.get(v).copied().unwrap_or(default)
+    }
+
     pub fn is_populated_region(&self, v: V2i) -> bool {
         self.grids.contains_key(&v)
     }
@@ -121,6 +215,149 @@ impl<T: Default> Region<T> {
     pub fn get_or_create(&mut self, v: V2i) -> &T {
         self.get_mut(v)  // NB: Downgrades
     }
+
+    /* Across loaded chunks only, in no particular chunk order (row-major within each chunk). */
+    pub fn iter_non_default(&self) -> impl Iterator<Item=(V2i, &T)> + '_
+        where
+            T: PartialEq
+    {
+        self.grids.values().flat_map(Grid::iter_non_default)
+    }
+
+    /* Only touches chunks already loaded; nothing is generated. */
+    pub fn fill_loaded(&mut self, value: T)
+        where
+            T: Clone
+    {
+        for grid in self.grids.values_mut() {
+            grid.iter_mut().for_each(|cell| *cell = value.clone());
+        }
+    }
+
+    /* Cells of chunks only in other are passed to combine as (None, new). */
+    pub fn merge<F>(&mut self, other: Region<T>, combine: F) -> Result<(), Error>
+        where
+            F: Fn(Option<T>, T) -> T
+    {
+        if other.grid_size != self.grid_size {
+            return Err(Error::MismatchedGridSize(self.grid_size, other.grid_size));
+        }
+
+        for (gi, mut grid) in other.grids {
+            match self.grids.get_mut(&gi) {
+                Some(existing) => {
+                    for (old, new) in existing.array.iter_mut().zip(grid.array.iter_mut()) {
+                        *old = combine(Some(std::mem::take(old)), std::mem::take(new));
+                    }
+                },
+                None => {
+                    for cell in grid.array.iter_mut() {
+                        *cell = combine(None, std::mem::take(cell));
+                    }
+                    self.grids.insert(gi, grid);
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/* Runs each generator for the chunk in turn, folding later grids into the first with combine. With
+ * no generators, chunks are default-filled. */
+pub fn chain_gen<T, F>(mut gens: Vec<GridGen<T>>, combine: F) -> GridGen<T>
+    where
+        T: Default + 'static,
+        F: Fn(&mut Grid<T>, &Grid<T>) + 'static
+{
+    Box::new(move |inv, gi, origin, dim| {
+        let mut layers = gens.iter_mut();
+        let mut acc = match layers.next() {
+            Some(gen) => gen(inv, gi, origin, dim),
+            None => return Grid::from_default(origin, dim).unwrap(),
+        };
+        for gen in layers {
+            combine(&mut acc, &gen(inv, gi, origin, dim));
+        }
+        acc
+    })
+}
+
+fn lattice_hash(seed: u64, v: V2i) -> f64 {
+    /* splitmix64 finalizer over the seed and lattice point */
+    let mut z = seed
+        ^ (v.0 as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ (v.1 as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn value_noise(seed: u64, scale: f64, v: V2i) -> f64 {
+    let p = V2f::from(v) / V2f(scale, scale);
+    let cell = V2f(p.0.floor(), p.1.floor());
+    let frac = p - cell;
+    let smooth = frac * frac * (V2f(3.0, 3.0) - frac * V2f(2.0, 2.0));
+    let base = V2i::from(cell);
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+    lerp(
+        lerp(lattice_hash(seed, base), lattice_hash(seed, base + V2i::X), smooth.0),
+        lerp(lattice_hash(seed, base + V2i::Y), lattice_hash(seed, base + V2i::ONE), smooth.0),
+        smooth.1,
+    )
+}
+
+/* Fills chunks with value noise in [0, 1) sampled at world coordinates, so a chunk regenerates
+ * identically for the same seed no matter when it is loaded. scale is the lattice spacing in cells,
+ * and must be positive (panics otherwise). */
+pub fn noise_gen(seed: u64, scale: f64) -> GridGen<f64> {
+    assert!(scale > 0.0, "noise scale must be positive, not {}", scale);
+    Box::new(move |_, _, origin, dim|
+        Grid::from_generator(|pt| value_noise(seed, scale, pt), origin, dim).unwrap()
+    )
+}
+
+#[cfg(feature = "serde")]
+impl<T> Region<T> {
+    fn chunk_path(dir: &Path, index: V2i) -> std::path::PathBuf {
+        dir.join(format!("{}_{}.chunk", index.0, index.1))
+    }
+
+    /* Writes every loaded chunk to dir/x_y.chunk (x, y being the Region coordinate), creating dir
+     * if needed. Unloaded chunks already on disk are left alone. */
+    pub fn save_dir(&self, dir: &Path) -> io::Result<()>
+        where
+            T: serde::Serialize
+    {
+        fs::create_dir_all(dir)?;
+        for (&index, grid) in &self.grids {
+            let mut file = io::BufWriter::new(fs::File::create(Self::chunk_path(dir, index))?);
+            serde_json::to_writer(&mut file, grid)?;
+            file.flush()?;  // NB: Dropping the writer would swallow errors from the final write
+        }
+        Ok(())
+    }
+
+    /* Replaces any loaded chunk at index and fires on_load. A chunk whose shape doesn't match
+     * its slot in this Region is rejected as InvalidData. */
+    pub fn load_chunk_from_dir(&mut self, dir: &Path, index: V2i) -> io::Result<()>
+        where
+            T: serde::de::DeserializeOwned
+    {
+        let file = io::BufReader::new(fs::File::open(Self::chunk_path(dir, index))?);
+        let grid: Grid<T> = serde_json::from_reader(file)?;
+        if grid.origin != index * self.grid_size || grid.dim != self.grid_size {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+                "chunk {:?} has origin {:?} and dim {:?}", index, grid.origin, grid.dim,
+            )));
+        }
+
+        self.grids.insert(index, grid);
+        if let Some(hook) = self.on_load.as_mut() {
+            hook(index);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -137,6 +374,224 @@ mod test {
         assert_eq!(r.grids(), 1);
     }
 
+    #[test]
+    fn non_square_chunks() {
+        let mut r = RegionConfig::<isize>::default().with_grid_size(V2i(16, 32))
+            .build().expect("Failed to build Region");
+        let cases = [
+            (V2i(5, 40), V2i(0, 1), V2i(5, 8)),
+            (V2i(-5, 40), V2i(-1, 1), V2i(11, 8)),
+            (V2i(-5, -1), V2i(-1, -1), V2i(11, 31)),
+            (V2i(5, -33), V2i(0, -2), V2i(5, 31)),
+            (V2i(-16, -32), V2i(-1, -1), V2i(0, 0)),
+            (V2i(15, -32), V2i(0, -1), V2i(15, 0)),
+        ];
+        for (i, &(p, gi, off)) in cases.iter().enumerate() {
+            assert_eq!(r.get_grid_index(p), gi, "index of {:?}", p);
+            assert_eq!(r.get_grid_offset(p), off, "offset of {:?}", p);
+            *r.get_mut(p) = i as isize + 1;
+            assert!(r.get_grid(p).unwrap().rect().contains(p));
+        }
+        for (i, &(p, _, _)) in cases.iter().enumerate() {
+            assert_eq!(r.get(p), Some(&(i as isize + 1)));
+        }
+        assert_eq!(r.grids(), 5);
+        assert_eq!(r.get(V2i(-5, -2)), Some(&0));
+    }
+
+    #[test]
+    fn iter_non_default() {
+        let mut r = RegionConfig::<isize>::default().with_grid_size(V2i(4, 4))
+            .build().expect("Failed to build Region");
+        *r.get_mut(V2i(1, 1)) = 3;
+        *r.get_mut(V2i(-5, 9)) = 4;
+        r.get_mut(V2i(20, 20));
+        *r.get_mut(V2i(2, 1)) = 0;
+        let mut cells: Vec<(V2i, isize)> = r.iter_non_default().map(|(pt, &t)| (pt, t)).collect();
+        cells.sort();
+        assert_eq!(cells, vec![(V2i(-5, 9), 4), (V2i(1, 1), 3)]);
+        assert_eq!(r.grids(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn save_and_load_dir() {
+        let dir = std::env::temp_dir().join(format!("rogue_util_region_{}", std::process::id()));
+        let mut r = RegionConfig::<isize>::default().with_grid_size(V2i(4, 4))
+            .build().expect("Failed to build Region");
+        *r.get_mut(V2i(1, 2)) = 5;
+        *r.get_mut(V2i(-3, -7)) = -2;
+        r.save_dir(&dir).expect("Failed to save");
+        assert!(dir.join("-1_-2.chunk").exists());
+
+        r.unload(V2i(-1, -2)).expect("Chunk wasn't loaded");
+        assert_eq!(r.get(V2i(-3, -7)), None);
+        r.load_chunk_from_dir(&dir, V2i(-1, -2)).expect("Failed to load");
+        assert_eq!(r.get(V2i(-3, -7)), Some(&-2));
+        assert_eq!(r.get(V2i(-4, -8)), Some(&0));
+        assert_eq!(r.get(V2i(1, 2)), Some(&5));
+        assert_eq!(r.grids(), 2);
+
+        assert_eq!(r.load_chunk_from_dir(&dir, V2i(7, 7)).unwrap_err().kind(), io::ErrorKind::NotFound);
+        let mut other = RegionConfig::<isize>::default().with_grid_size(V2i(2, 2))
+            .build().expect("Failed to build Region");
+        assert_eq!(other.load_chunk_from_dir(&dir, V2i(0, 0)).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_dir_all(&dir).expect("Failed to clean up");
+    }
+
+    #[test]
+    fn get_copied() {
+        let mut r = RegionConfig::<isize>::default().build().expect("Failed to build Region");
+        *r.get_mut(V2i(1, 1)) = 7;
+        assert_eq!(r.get_copied(V2i(1, 1), -1), 7);
+        assert_eq!(r.get_copied(V2i(2, 2), -1), 0);
+        assert_eq!(r.get_copied(r.grid_size() * V2i(3, 3), -1), -1);
+        assert_eq!(r.grids(), 1);
+    }
+
+    #[test]
+    fn clone() {
+        let mut r = RegionConfig::<isize>::default().with_generator(|_, _, o, d|
+            Grid::from_generator(|_| 1, o, d).expect("Failed to generate Grid")
+        ).build().expect("Failed to build Region");
+        *r.get_mut(V2i(3, 3)) = 5;
+        *r.get_mut(V2i(-1, 40)) = 6;
+
+        let mut c = r.clone();
+        assert!(c.grid_gen.is_none());
+        assert_eq!(c.grids(), 2);
+        assert_eq!(c.get(V2i(3, 3)), Some(&5));
+        assert_eq!(c.get(V2i(-1, 40)), Some(&6));
+        assert_eq!(c.get(V2i(0, 0)), Some(&1));
+        assert_eq!(*c.get_or_create(V2i(100, 100)), 0);
+        assert_eq!(*r.get_or_create(V2i(100, 100)), 1);
+    }
+
+    #[test]
+    fn fill_loaded() {
+        let mut r = RegionConfig::<isize>::default().build().expect("Failed to build Region");
+        let gs = r.grid_size();
+        for &pt in &[V2i(0, 0), gs * V2i(2, 0), gs * V2i(-1, 5)] {
+            *r.get_mut(pt) = 3;
+        }
+        r.fill_loaded(9);
+        assert_eq!(r.grids(), 3);
+        assert_eq!(r.get(gs * V2i(2, 0) + V2i(1, 1)), Some(&9));
+        assert_eq!(r.get(gs * V2i(-1, 5)), Some(&9));
+        assert_eq!(r.get(gs * V2i(1, 0)), None);
+    }
+
+    #[test]
+    fn merge() {
+        let mut ra = RegionConfig::<isize>::default().build().expect("Failed to build Region");
+        let mut rb = RegionConfig::<isize>::default().build().expect("Failed to build Region");
+        let gs = ra.grid_size();
+        let neg = -gs;
+        *ra.get_mut(V2i(0, 0)) = 1;
+        *ra.get_mut(gs) = 2;
+        *rb.get_mut(V2i(0, 0)) = 10;
+        *rb.get_mut(V2i(1, 0)) = 20;
+        *rb.get_mut(neg) = 30;
+
+        ra.merge(rb, |old, new| old.unwrap_or(100) + new).expect("Failed to merge");
+        assert_eq!(ra.grids(), 3);
+        assert_eq!(ra.get(V2i(0, 0)), Some(&11));
+        assert_eq!(ra.get(V2i(1, 0)), Some(&20));
+        assert_eq!(ra.get(V2i(2, 0)), Some(&0));
+        assert_eq!(ra.get(gs), Some(&2));
+        assert_eq!(ra.get(neg), Some(&130));
+        assert_eq!(ra.get(neg + V2i(1, 0)), Some(&100));
+    }
+
+    #[test]
+    fn merge_mismatched() {
+        let mut ra = RegionConfig::<isize>::default().build().expect("Failed to build Region");
+        let rb = RegionConfig::<isize>::default().with_grid_size(V2i(8, 8)).build().expect("Failed to build Region");
+        assert!(ra.merge(rb, |_, new| new).is_err());
+    }
+
+    #[test]
+    fn into_region() {
+        let grid = Grid::from_generator(|pt: V2i| pt.0 + pt.1, V2i(-4, 8), V2i(4, 2)).unwrap();
+        let r = grid.into_region().expect("Failed to wrap grid");
+        assert_eq!(r.grid_size(), V2i(4, 2));
+        assert_eq!(r.grids(), 1);
+        assert_eq!(r.get(V2i(-3, 9)), Some(&6));
+        assert_eq!(r.get(V2i(0, 9)), None);
+
+        let grid: Grid<isize> = Grid::from_default(V2i(1, 0), V2i(4, 2)).unwrap();
+        assert!(matches!(grid.into_region(), Err(Error::MisalignedGrid(..))));
+    }
+
+    #[test]
+    fn load_hooks() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let loads = Rc::new(RefCell::new(Vec::new()));
+        let unloads = Rc::new(RefCell::new(Vec::new()));
+        let (l, u) = (loads.clone(), unloads.clone());
+        let mut r = RegionConfig::<isize>::default()
+            .on_load(move |gi| l.borrow_mut().push(gi))
+            .on_unload(move |gi| u.borrow_mut().push(gi))
+            .build().expect("Failed to build Region");
+        let gs = r.grid_size();
+
+        r.get_mut(V2i(1, 1));
+        r.get_mut(V2i(2, 2));
+        r.get_mut(gs * V2i(-1, 2));
+        assert_eq!(*loads.borrow(), vec![V2i(0, 0), V2i(-1, 2)]);
+
+        assert!(r.unload(V2i(-1, 2)).is_some());
+        assert!(r.unload(V2i(5, 5)).is_none());
+        assert_eq!(*unloads.borrow(), vec![V2i(-1, 2)]);
+
+        r.get_mut(gs * V2i(-1, 2));
+        assert_eq!(loads.borrow().len(), 3);
+    }
+
+    #[test]
+    fn chained_generators() {
+        let gens: Vec<GridGen<isize>> = vec![
+            Box::new(|_, _, o, d| Grid::from_generator(|_| 10, o, d).unwrap()),
+            Box::new(|_, _, o, d| Grid::from_generator(|pt: V2i| pt.0, o, d).unwrap()),
+        ];
+        let mut r = RegionConfig::<isize>::default().with_grid_gen(Some(chain_gen(gens, |acc, layer| {
+            for (a, l) in acc.iter_mut().zip(layer.iter()) {
+                *a += *l;
+            }
+        }))).build().expect("Failed to build Region");
+        assert_eq!(*r.get_or_create(V2i(3, 1)), 13);
+        assert_eq!(*r.get_or_create(V2i(-5, 40)), 5);
+
+        let mut empty = RegionConfig::<isize>::default()
+            .with_grid_gen(Some(chain_gen(Vec::new(), |_, _| ())))
+            .build().expect("Failed to build Region");
+        assert_eq!(*empty.get_or_create(V2i(3, 1)), 0);
+    }
+
+    #[test]
+    fn noise_deterministic() {
+        let mut r = RegionConfig::<f64>::default().with_grid_gen(Some(noise_gen(42, 7.5))).build().expect("Failed to build Region");
+        let pt = V2i(-40, 70);
+        let first = r.get_grid_mut(pt).clone();
+        assert!(r.unload(r.get_grid_index(pt)).is_some());
+        assert_eq!(r.grids(), 0);
+        let second = r.get_grid_mut(pt).clone();
+        assert_eq!(first.array(), second.array());
+        assert!(first.iter().all(|v| (0.0..1.0).contains(v)));
+        assert!(first.iter().any(|v| *v != first.array()[0]));
+
+        let mut other = RegionConfig::<f64>::default().with_grid_gen(Some(noise_gen(43, 7.5))).build().expect("Failed to build Region");
+        assert_ne!(other.get_grid_mut(pt).array(), first.array());
+    }
+
+    #[test]
+    #[should_panic]
+    fn noise_needs_positive_scale() {
+        let _ = noise_gen(42, 0.0);
+    }
+
     const SIZE: isize = 5;
 
     #[test]
@@ -186,6 +641,16 @@ mod test {
         println!("{:?}", r);
     }
 
+    #[test]
+    fn generator_closure() {
+        let mut r = RegionConfig::<isize>::default().with_generator(|_, r, o, d|
+            Grid::from_generator(|_| r.0 - r.1, o, d).expect("Failed to generate Grid")
+        ).build().expect("Failed to build Region");
+
+        assert_eq!(*r.get_or_create(r.grid_size() * V2i(3, 1)), 2);
+        assert_eq!(r.grids(), 1);
+    }
+
     #[test]
     fn generator_invoker() {
         let mut r = RegionConfig::<isize>::default().with_grid_gen(Some(Box::new(|i, _, o, d|
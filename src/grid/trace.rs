@@ -0,0 +1,313 @@
+use crate::*;
+use super::{Grid, path::{L2, Traversable}};
+
+use std::convert::TryInto;
+use std::f64::consts::PI;
+
+impl<T> Grid<T> {
+    /* Marches the supercover line (as cast_to_wall) from `from` (exclusive) toward
+     * `from + dir * max`, stopping after `max` cells or at the first cell off the grid. The far end
+     * is pulled in to just past the grid, so a huge max costs nothing, and dir is reduced to lowest
+     * terms; if even then that end doesn't fit in Vi, nothing is hit. */
+    pub fn raycast<F>(&self, from: V2i, dir: V2i, max: usize, hit: F) -> Option<(V2i, &T)>
+        where
+            F: Fn(&T) -> bool
+    {
+        if dir == V2i::ZERO {
+            return None;
+        }
+
+        /* NB: i128 so neither the reach nor the far end can overflow before being checked */
+        let (mut a, mut b) = (dir.0.unsigned_abs(), dir.1.unsigned_abs());
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        let dir = (dir.0 as i128 / a as i128, dir.1 as i128 / a as i128);
+        let (lo, hi) = (self.rect().origin(), self.rect().opp());
+        let gap = |a: Vi, b: Vi| (a as i128 - b as i128).abs();
+        let reach = gap(from.0, lo.0).max(gap(from.0, hi.0)).max(gap(from.1, lo.1)).max(gap(from.1, hi.1));
+        let steps = (max as i128).min(reach + 1);
+        let end = V2i(
+            (from.0 as i128 + dir.0 * steps).try_into().ok()?,
+            (from.1 as i128 + dir.1 * steps).try_into().ok()?,
+        );
+
+        for pt in raster::supercover(from, end).skip(1).take(max) {
+            let tile = self.get(pt).ok()?;
+            if hit(tile) {
+                return Some((pt, tile));
+            }
+        }
+        None
+    }
+
+    fn paint_cells<I>(&mut self, cells: I, value: T)
+        where
+            I: IntoIterator<Item=V2i>,
+            T: Clone
+    {
+        for pt in cells {
+            if let Ok(tile) = self.get_mut(pt) {
+                *tile = value.clone();
+            }
+        }
+    }
+
+    /* Cells of the line outside the grid are skipped. */
+    pub fn draw_line(&mut self, a: V2i, b: V2i, value: T)
+        where
+            T: Clone
+    {
+        self.paint_cells(raster::line(a, b), value);
+    }
+
+    /* The outline of raster::ring under L2, clipped to the grid. */
+    pub fn paint_circle(&mut self, center: V2i, radius: Vi, value: T)
+        where
+            T: Clone
+    {
+        self.paint_cells(raster::ring::<L2>(center, radius), value);
+    }
+
+    /* The filled raster::triangle, clipped to the grid. */
+    pub fn fill_triangle(&mut self, a: V2i, b: V2i, c: V2i, value: T)
+        where
+            T: Clone
+    {
+        self.paint_cells(raster::triangle(a, b, c), value);
+    }
+
+    /* The filled raster::ball under L2, clipped to the grid. */
+    pub fn paint_disk(&mut self, center: V2i, radius: Vi, value: T)
+        where
+            T: Clone
+    {
+        self.paint_cells(raster::ball::<L2>(center, radius), value);
+    }
+}
+
+impl<T: Traversable> Grid<T> {
+    /* The farthest cell along the supercover line from `from` toward `to` reachable before the
+     * first impassable (or out of bounds) cell; `from` itself is never checked. */
+    pub fn cast_to_wall(&self, from: V2i, to: V2i) -> V2i {
+        let mut last = from;
+        for pt in raster::supercover(from, to).skip(1) {
+            if !self.get(pt).is_ok_and(|t| t.can_pass()) {
+                break;
+            }
+            last = pt;
+        }
+        last
+    }
+
+    /* Casts rays from the center of origin toward every nearby wall corner (and just to either
+     * side of it), plus a fan of rays to trace the radius, returning the hit points in increasing
+     * angle in (-pi, pi]. Cells outside the grid count as walls. */
+    pub fn visibility_polygon(&self, origin: V2i, radius: Vi) -> Vec<V2f> {
+        const EPS: Vf = 1e-4;
+        const FAN: usize = 64;
+
+        let eye = V2f::from(origin) + V2f(0.5, 0.5);
+        let bounds = R2i::origin_dim(origin - V2i(radius + 1, radius + 1), V2i(2 * radius + 3, 2 * radius + 3));
+        let open = |pt: V2i| self.get(pt).is_ok_and(|t| t.can_pass());
+        let mut segs = Vec::new();
+        for pt in bounds.iter().filter(|&pt| !open(pt)) {
+            /* Only faces toward an open cell can ever be seen */
+            let c = V2f::from(pt);
+            let corners = [c, c + V2f::X, c + V2f::ONE, c + V2f::Y];
+            for (i, d) in [V2i(0, -1), V2i(1, 0), V2i(0, 1), V2i(-1, 0)].iter().enumerate() {
+                if open(pt + *d) {
+                    segs.push((corners[i], corners[(i + 1) % 4]));
+                }
+            }
+        }
+
+        let mut angles: Vec<Vf> = (1..=FAN).map(|i| i as Vf * 2.0 * PI / FAN as Vf - PI).collect();
+        for p in segs.iter().flat_map(|&(p, q)| vec![p, q]) {
+            let a = (p - eye).ang();
+            for &a in &[a - EPS, a, a + EPS] {
+                angles.push(if a <= -PI { a + 2.0 * PI } else if a > PI { a - 2.0 * PI } else { a });
+            }
+        }
+        angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        angles.dedup();
+
+        let hits = angles.into_iter().map(|a| {
+            let d = V2f(a.cos(), a.sin());
+            let mut t = radius as Vf;
+            for &(p, q) in &segs {
+                let e = q - p;
+                let denom = d.cross(e);
+                if denom.abs() < 1e-12 {
+                    continue;
+                }
+                let w = p - eye;
+                let (hit, u) = (w.cross(e) / denom, w.cross(d) / denom);
+                if hit > 0.0 && hit < t && (0.0..=1.0).contains(&u) {
+                    t = hit;
+                }
+            }
+            eye + d * V2f(t, t)
+        });
+
+        /* Drop vertices lying along a straight run of wall, which the corner rays produce plenty of */
+        let mut poly: Vec<V2f> = Vec::new();
+        for v in hits {
+            if poly.len() >= 2 {
+                let (a, b) = (poly[poly.len() - 2], poly[poly.len() - 1]);
+                if (b - a).cross(v - b).abs() < 1e-9 {
+                    poly.pop();
+                }
+            }
+            poly.push(v);
+        }
+        poly
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn testing_grid() -> Grid<isize> {
+        Grid::from_vec(
+            vec![
+                0, 0, 0, 0, 0,
+                0, 0, 0, 1, 0,
+                0, 0, 0, 0, 0,
+                1, 0, 0, 0, 2,
+                0, 0, 0, 0, 0,
+            ], V2i(0, 0), V2i(5, 5),
+        ).expect("Creating the test grid failed")
+    }
+
+    #[test]
+    fn raycast_hits() {
+        let grid = testing_grid();
+        assert_eq!(grid.raycast(V2i(4, 1), V2i(-1, 0), 10, |t| *t != 0), Some((V2i(3, 1), &1)));
+        assert_eq!(grid.raycast(V2i(2, 3), V2i(1, 0), 10, |t| *t != 0), Some((V2i(4, 3), &2)));
+        assert_eq!(grid.raycast(V2i(3, 3), V2i(-1, 0), 10, |t| *t != 0), Some((V2i(0, 3), &1)));
+        assert_eq!(grid.raycast(V2i(1, 3), V2i(1, -1), 3, |t| *t != 0), Some((V2i(3, 1), &1)));
+    }
+
+    #[test]
+    fn raycast_misses() {
+        let grid = testing_grid();
+        assert_eq!(grid.raycast(V2i(3, 3), V2i(-1, 0), 2, |t| *t != 0), None);
+        assert_eq!(grid.raycast(V2i(0, 0), V2i(0, 1), 10, |t| *t == 2), None);
+        assert_eq!(grid.raycast(V2i(3, 1), V2i(0, 1), 10, |t| *t != 0), None);
+        assert_eq!(grid.raycast(V2i(2, 2), V2i(0, 0), 10, |_| true), None);
+    }
+
+    #[test]
+    fn raycast_huge() {
+        let grid = testing_grid();
+        assert_eq!(grid.raycast(V2i(0, 1), V2i(1, 0), usize::MAX, |t| *t != 0), Some((V2i(3, 1), &1)));
+        assert_eq!(grid.raycast(V2i(0, 1), V2i(Vi::MAX, 0), usize::MAX, |t| *t != 0), Some((V2i(3, 1), &1)));
+        assert_eq!(grid.raycast(V2i(1, 3), V2i(Vi::MIN, 0), usize::MAX, |t| *t != 0), Some((V2i(0, 3), &1)));
+        assert_eq!(grid.raycast(V2i(2, 2), V2i(Vi::MAX, Vi::MIN), usize::MAX, |t| *t != 0), None);  // NB: Far end overflows
+        assert_eq!(grid.raycast(V2i(2, 2), V2i(Vi::MAX - 1, 1 - Vi::MAX), usize::MAX, |t| *t != 0), Some((V2i(3, 1), &1)));
+        assert_eq!(grid.raycast(V2i(1, 0), V2i(-1, 1), usize::MAX, |_| false), None);
+    }
+
+    #[test]
+    fn raycast_shallow() {
+        let grid = testing_grid();
+        /* The supercover steps one axis at a time, so no cell is skipped at a corner. */
+        assert_eq!(grid.raycast(V2i(0, 1), V2i(2, 1), 10, |t| *t != 0), Some((V2i(4, 3), &2)));
+        assert_eq!(grid.raycast(V2i(0, 4), V2i(3, -1), 10, |t| *t == 1), None);
+    }
+
+    #[test]
+    fn cast_to_wall() {
+        let grid = testing_grid();
+        assert_eq!(grid.cast_to_wall(V2i(0, 1), V2i(4, 1)), V2i(2, 1));
+        assert_eq!(grid.cast_to_wall(V2i(0, 3), V2i(4, 3)), V2i(3, 3));
+        assert_eq!(grid.cast_to_wall(V2i(4, 1), V2i(0, 1)), V2i(4, 1));
+        assert_eq!(grid.cast_to_wall(V2i(1, 3), V2i(2, 3)), V2i(2, 3));
+        assert_eq!(grid.cast_to_wall(V2i(2, 2), V2i(2, 9)), V2i(2, 4));
+    }
+
+    #[test]
+    fn draw_line_clips() {
+        let mut grid = testing_grid();
+        grid.draw_line(V2i(-2, 2), V2i(6, 2), 7);
+        for x in 0..5 {
+            assert_eq!(*grid.get(V2i(x, 2)).unwrap(), 7);
+        }
+        assert_eq!(grid.iter().filter(|&&t| t == 7).count(), 5);
+    }
+
+    #[test]
+    fn paint_disk_clips() {
+        let mut grid: Grid<isize> = Grid::from_default(V2i(0, 0), V2i(5, 5)).unwrap();
+        grid.paint_disk(V2i(0, 0), 2, 7);
+        for pt in grid.rect().iter() {
+            let expected = if L2::dist(pt, V2i(0, 0)) <= 2 { 7 } else { 0 };
+            assert_eq!(*grid.get(pt).unwrap(), expected, "at {:?}", pt);
+        }
+        assert_eq!(grid.iter().filter(|&&t| t == 7).count(), 6);
+    }
+
+    #[test]
+    fn fill_triangle_clips() {
+        let mut grid: Grid<isize> = Grid::from_default(V2i(0, 0), V2i(5, 5)).unwrap();
+        grid.fill_triangle(V2i(2, -3), V2i(8, 3), V2i(2, 3), 7);
+        for pt in grid.rect().iter() {
+            let inside = pt.0 >= 2 && pt.1 <= 3 && pt.0 - pt.1 <= 5;
+            assert_eq!(*grid.get(pt).unwrap(), if inside { 7 } else { 0 }, "at {:?}", pt);
+        }
+        assert_eq!(*grid.get(V2i(1, 1)).unwrap(), 0);
+        assert_eq!(*grid.get(V2i(4, 4)).unwrap(), 0);
+        assert_eq!(grid.iter().filter(|&&t| t == 7).count(), 12);
+    }
+
+    #[test]
+    fn paint_circle_clips() {
+        let mut grid: Grid<isize> = Grid::from_default(V2i(0, 0), V2i(5, 5)).unwrap();
+        grid.paint_circle(V2i(2, 2), 3, 7);
+        assert_eq!(*grid.get(V2i(2, 2)).unwrap(), 0);
+        assert_eq!(*grid.get(V2i(0, 0)).unwrap(), 7);
+        assert_eq!(*grid.get(V2i(4, 2)).unwrap(), 0);
+        assert_eq!(grid.iter().filter(|&&t| t == 7).count(), 12);
+    }
+
+    #[test]
+    fn visibility_polygon_notch() {
+        let mut room: Grid<isize> = Grid::from_generator(|pt: V2i|
+            if pt.0 == 0 || pt.1 == 0 || pt.0 == 12 || pt.1 == 12 { 1 } else { 0 },
+            V2i(0, 0), V2i(13, 13),
+        ).unwrap();
+        let eye = V2f(4.5, 6.5);
+        let inside = |poly: &[V2f], p: V2f| {
+            let mut crossings = 0;
+            for i in 0..poly.len() {
+                let (a, b) = (poly[i], poly[(i + 1) % poly.len()]);
+                if (a.1 > p.1) != (b.1 > p.1) && p.0 < a.0 + (p.1 - a.1) / (b.1 - a.1) * (b.0 - a.0) {
+                    crossings += 1;
+                }
+            }
+            crossings % 2 == 1
+        };
+
+        let open = room.visibility_polygon(V2i(4, 6), 20);
+        assert!(inside(&open, V2f(10.5, 6.5)));
+        assert!(!inside(&open, V2f(12.5, 6.5)));
+
+        room.set(V2i(8, 6), 1).unwrap();
+        let poly = room.visibility_polygon(V2i(4, 6), 20);
+        println!("polygon: {:?}", poly);
+        assert!(inside(&poly, V2f(7.5, 6.5)));
+        assert!(!inside(&poly, V2f(8.5, 6.5)));
+        assert!(!inside(&poly, V2f(10.5, 6.5)));
+        assert!(inside(&poly, V2f(10.5, 3.5)));
+        assert!(inside(&poly, V2f(10.5, 9.5)));
+        for pair in poly.windows(2) {
+            assert!((pair[0] - eye).ang() <= (pair[1] - eye).ang() + 1e-9);
+        }
+
+        let small = room.visibility_polygon(V2i(4, 6), 2);
+        assert!(small.iter().all(|v| (*v - eye).l2() <= 2.0 + 1e-9));
+        assert!(small.iter().any(|v| ((*v - eye).l2() - 2.0).abs() < 1e-9));
+    }
+}
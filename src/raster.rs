@@ -31,8 +31,15 @@ impl Iterator for BresenhamLineIter {
             Some(if self.swap { pt.swap() } else { pt })
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.dist.max(0) as usize;
+        (n, Some(n))
+    }
 }
 
+impl ExactSizeIterator for BresenhamLineIter {}
+
 pub fn line(a: V2i, b: V2i) -> BresenhamLineIter {
     let dab = a - b;
     
@@ -57,9 +64,93 @@ pub fn line(a: V2i, b: V2i) -> BresenhamLineIter {
     }
 }
 
+/* As line, but into a caller-owned buffer (cleared first) so its allocation can be reused. */
+pub fn line_into(a: V2i, b: V2i, out: &mut Vec<V2i>) {
+    out.clear();
+    out.extend(line(a, b));
+}
+
+/* Walks the cells crossed by the segment between cell centers, starting at a and stepping one
+ * axis at a time; a segment passing exactly through a cell corner steps diagonally. */
+#[derive(Debug, Clone)]
+pub struct SupercoverIter {
+    current: V2i,
+    step: V2i,
+    count: V2i,
+    taken: V2i,
+    done: bool,
+}
+
+impl Iterator for SupercoverIter {
+    type Item = V2i;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let pt = self.current;
+        if self.taken.0 >= self.count.0 && self.taken.1 >= self.count.1 {
+            self.done = true;
+            return Some(pt);
+        }
+
+        let decision = (1 + 2 * self.taken.0) * self.count.1 - (1 + 2 * self.taken.1) * self.count.0;
+        if decision <= 0 {
+            self.current.0 += self.step.0;
+            self.taken.0 += 1;
+        }
+        if decision >= 0 {
+            self.current.1 += self.step.1;
+            self.taken.1 += 1;
+        }
+        Some(pt)
+    }
+}
+
+pub fn supercover(a: V2i, b: V2i) -> SupercoverIter {
+    let d = b - a;
+    SupercoverIter {
+        current: a,
+        step: V2i(d.0.signum(), d.1.signum()),
+        count: d.abs(),
+        taken: V2i::ZERO,
+        done: false,
+    }
+}
+
+/* The endpoints are floored to the cells containing them. */
+pub fn line_from_f(a: V2f, b: V2f) -> SupercoverIter {
+    let cell = |v: V2f| V2i::from(V2f(v.0.floor(), v.1.floor()));
+    supercover(cell(a), cell(b))
+}
+
+fn bounds(center: V2i, radius: Vi) -> R2i {
+    R2i::origin_dim(center - V2i(radius, radius), V2i(2 * radius + 1, 2 * radius + 1))
+}
+
+/* Every supported metric dominates Linf, so the bounding square is enough to search. */
+pub fn ball<M: Metric>(center: V2i, radius: Vi) -> impl Iterator<Item=V2i> {
+    bounds(center, radius.max(0)).iter().filter(move |&pt| M::dist(pt, center) <= radius)
+}
+
+pub fn ring<M: Metric>(center: V2i, radius: Vi) -> impl Iterator<Item=V2i> {
+    bounds(center, radius.max(0)).iter().filter(move |&pt| M::dist(pt, center) == radius)
+}
+
+/* Cells inside or on the edges of the triangle, in row-major order; either winding works, and a
+ * degenerate triangle gives the lattice points on its segment. */
+pub fn triangle(a: V2i, b: V2i, c: V2i) -> impl Iterator<Item=V2i> {
+    let orient = if (b - a).cross(c - a) < 0 { -1 } else { 1 };
+    let (lo, hi) = (a.min(b).min(c), a.max(b).max(c));
+    R2i::origin_opp(lo, hi + V2i::ONE).iter().filter(move |&pt| {
+        [(a, b), (b, c), (c, a)].iter().all(|&(p, q)| (q - p).cross(pt - p) * orient >= 0)
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::grid::path::{L1, L2, Linf};
 
     const SIZE: Vi = 5;
 
@@ -107,4 +198,83 @@ mod test {
             assert!(pts.contains(&b));
         }
     }
+
+    #[test]
+    fn supercover_connected() {
+        for &(a, b) in &[
+            (V2i(0, 0), V2i(SIZE, 2*SIZE)),
+            (V2i(3, -1), V2i(-2*SIZE, SIZE)),
+            (V2i(0, 0), V2i(SIZE, SIZE)),
+            (V2i(1, 1), V2i(1, 1)),
+            (V2i(0, 0), V2i(0, -SIZE)),
+        ] {
+            let pts: Vec<_> = supercover(a, b).collect();
+            println!("{:?} to {:?}: {:?}", a, b, pts);
+            assert_eq!(pts.first(), Some(&a));
+            assert_eq!(pts.last(), Some(&b));
+            for pair in pts.windows(2) {
+                assert_eq!((pair[1] - pair[0]).linf(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn line_from_f_edges() {
+        let pts: Vec<_> = line_from_f(V2f(1.999, 0.5), V2f(5.5, 0.5)).collect();
+        assert_eq!(pts.first(), Some(&V2i(1, 0)));
+        assert_eq!(pts.len(), 5);
+        let pts: Vec<_> = line_from_f(V2f(2.0, 0.5), V2f(5.5, 0.5)).collect();
+        assert_eq!(pts.first(), Some(&V2i(2, 0)));
+        let pts: Vec<_> = line_from_f(V2f(-0.001, -0.5), V2f(3.0, 3.0)).collect();
+        assert_eq!(pts.first(), Some(&V2i(-1, -1)));
+        assert_eq!(pts.last(), Some(&V2i(3, 3)));
+    }
+
+    fn check_ball<M: Metric>(norm: fn(V2i) -> bool, ring_norm: fn(V2i) -> bool) {
+        let center = V2i(3, -2);
+        let pts: Vec<_> = ball::<M>(center, SIZE).collect();
+        println!("ball: {:?}", pts);
+        for pt in &R2i::origin_dim(center - V2i(2 * SIZE, 2 * SIZE), V2i(4 * SIZE, 4 * SIZE)) {
+            assert_eq!(pts.contains(&pt), norm(pt - center));
+            assert_eq!(ring::<M>(center, SIZE).any(|p| p == pt), ring_norm(pt - center));
+        }
+    }
+
+    #[test]
+    fn balls() {
+        check_ball::<L1>(|v| v.l1() <= SIZE, |v| v.l1() == SIZE);
+        check_ball::<Linf>(|v| v.linf() <= SIZE, |v| v.linf() == SIZE);
+        check_ball::<L2>(
+            |v| v.l2_sq() <= SIZE * SIZE,
+            |v| v.l2_sq() <= SIZE * SIZE && v.l2_sq() > (SIZE - 1) * (SIZE - 1),
+        );
+        assert_eq!(ball::<L1>(V2i(0, 0), 0).collect::<Vec<_>>(), vec![V2i(0, 0)]);
+        assert_eq!(ball::<L2>(V2i(0, 0), -1).count(), 0);
+    }
+
+    #[test]
+    fn line_into_matches_line() {
+        let mut buf = vec![V2i(99, 99); 3];
+        for &(a, b) in &[
+            (V2i(0, 0), V2i(7, 3)), (V2i(2, 5), V2i(-4, -1)), (V2i(1, 1), V2i(1, 1)),
+            (V2i(0, 4), V2i(0, -4)), (V2i(-3, 2), V2i(5, 2)),
+        ] {
+            line_into(a, b, &mut buf);
+            assert_eq!(buf, line(a, b).collect::<Vec<_>>());
+            assert_eq!(line(a, b).len(), buf.len());
+        }
+    }
+
+    #[test]
+    fn triangle_cells() {
+        let (a, b, c) = (V2i(0, 0), V2i(4, 0), V2i(0, 4));
+        let cells: Vec<V2i> = triangle(a, b, c).collect();
+        assert_eq!(cells.len(), 15);
+        assert!(cells.iter().all(|pt| pt.0 >= 0 && pt.1 >= 0 && pt.0 + pt.1 <= 4));
+        assert_eq!(triangle(a, c, b).collect::<Vec<_>>(), cells);
+        assert_eq!(triangle(c, a, b).collect::<Vec<_>>(), cells);
+
+        assert_eq!(triangle(V2i(1, 1), V2i(1, 1), V2i(1, 1)).collect::<Vec<_>>(), vec![V2i(1, 1)]);
+        assert_eq!(triangle(V2i(0, 0), V2i(2, 2), V2i(4, 4)).collect::<Vec<_>>(), vec![V2i(0, 0), V2i(1, 1), V2i(2, 2), V2i(3, 3), V2i(4, 4)]);
+    }
 }